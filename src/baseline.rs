@@ -0,0 +1,362 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{cli, cmd, report};
+
+/// Version of the baseline file, incremented for incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Key {
+    /// Path of the file relative to the configuration file, using '/' as separator.
+    file: String,
+    check: String,
+    /// Line-insensitive fingerprint of the finding.
+    fingerprint: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Finding {
+    #[serde(flatten)]
+    key: Key,
+    /// Message of the finding, only stored for readability.
+    message: String,
+    /// Number of occurrences of this finding.
+    count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Model {
+    format_version: u32,
+    findings: Vec<Finding>,
+}
+
+/// Provides keys for diagnostics, caching the contents of the referenced source files.
+struct Keys {
+    lines: HashMap<path::PathBuf, Vec<String>>,
+}
+
+impl Keys {
+    fn new() -> Keys {
+        Keys {
+            lines: HashMap::new(),
+        }
+    }
+
+    fn source_line(&mut self, file: &path::Path, line: u32) -> String {
+        let lines = self.lines.entry(file.to_path_buf()).or_insert_with(|| {
+            fs::read(file)
+                .map(|content| {
+                    String::from_utf8_lossy(&content)
+                        .lines()
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        });
+
+        // whitespace is normalized such that re-indenting code does not change the fingerprint
+        lines
+            .get((line as usize).saturating_sub(1))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_default()
+    }
+
+    /// Builds the key for a diagnostic reported for the analyzed file `path`, using a path
+    /// relative to the `root` of the configuration. Diagnostics without a location are
+    /// associated with the analyzed file.
+    fn key(&mut self, diagnostic: &cmd::Diagnostic, path: &path::Path, root: &path::Path) -> Key {
+        let check = diagnostic.check.clone().unwrap_or_default();
+        let (file, source) = match &diagnostic.location {
+            Some(loc) => (loc.file.as_path(), self.source_line(&loc.file, loc.line)),
+            None => (path, String::new()),
+        };
+
+        let mut hasher = Sha256::new();
+        for part in [check.as_str(), diagnostic.message.as_str(), source.as_str()] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        let fingerprint = hasher.finalize()[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+
+        let file = cli::utils::relative_to(file, root).unwrap_or(file.to_path_buf());
+        Key {
+            file: file.to_string_lossy().replace('\\', "/"),
+            check,
+            fingerprint,
+        }
+    }
+}
+
+/// Identifies a diagnostic independent of the translation unit it has been reported for.
+fn identity(diagnostic: &cmd::Diagnostic) -> impl Eq + std::hash::Hash {
+    (
+        diagnostic.location.clone(),
+        diagnostic.check.clone(),
+        diagnostic.message.clone(),
+    )
+}
+
+fn is_finding(diagnostic: &cmd::Diagnostic) -> bool {
+    matches!(
+        diagnostic.severity,
+        cmd::Severity::Error | cmd::Severity::Warning
+    )
+}
+
+/// Known findings loaded from a baseline file.
+pub struct Baseline {
+    model: Model,
+}
+
+impl Baseline {
+    pub fn load(file: &path::Path) -> eyre::Result<Baseline> {
+        Ok(Baseline { model: load(file)? })
+    }
+}
+
+fn load(file: &path::Path) -> eyre::Result<Model> {
+    let name = file.to_string_lossy();
+    let content = fs::read_to_string(file)
+        .wrap_err(format!("Failed to read baseline '{name}'"))
+        .suggestion("Use the subcommand 'baseline update' to create a baseline")?;
+
+    let model: Model = serde_json::from_str(&content)
+        .wrap_err(format!("Invalid baseline '{name}'"))
+        .suggestion("Use the subcommand 'baseline update' to re-create the baseline")?;
+
+    if model.format_version != FORMAT_VERSION {
+        return Err(eyre!(
+            "Unsupported baseline format version {}",
+            model.format_version
+        ))
+        .suggestion("Use the subcommand 'baseline update' to re-create the baseline");
+    }
+    Ok(model)
+}
+
+/// Records all findings of the provided records in the baseline file. The files of the findings
+/// are relative to the root of the configuration of each record, provided by `roots`.
+pub fn update(
+    file: &path::Path,
+    records: &[report::Record],
+    roots: &[&path::Path],
+) -> eyre::Result<usize> {
+    let mut keys = Keys::new();
+    let mut seen = HashSet::new();
+    let mut findings: BTreeMap<Key, Finding> = BTreeMap::new();
+
+    for record in records.iter() {
+        let output = match &record.result {
+            cmd::RunResult::Ok => continue,
+            cmd::RunResult::Err(output)
+            | cmd::RunResult::Warn(output)
+            | cmd::RunResult::Timeout(output) => output,
+        };
+
+        for diagnostic in output.diagnostics.iter().filter(|d| is_finding(d)) {
+            // findings in headers are reported for each translation unit
+            if !seen.insert(identity(diagnostic)) {
+                continue;
+            }
+            let key = keys.key(diagnostic, &record.path, roots[record.config]);
+            findings
+                .entry(key.clone())
+                .or_insert(Finding {
+                    key,
+                    message: diagnostic.message.clone(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+    }
+
+    let model = Model {
+        format_version: FORMAT_VERSION,
+        findings: findings.into_values().collect(),
+    };
+    let count = model.findings.iter().map(|f| f.count).sum();
+
+    fs::write(file, serde_json::to_string_pretty(&model).unwrap() + "\n")
+        .wrap_err(format!(
+            "Failed to write baseline '{}'",
+            file.to_string_lossy()
+        ))
+        .suggestion("Please make sure that the parent directory of the baseline exists")?;
+    Ok(count)
+}
+
+/// Result of applying a baseline to a set of records.
+pub struct Applied {
+    /// Number of findings that have been suppressed by the baseline.
+    pub suppressed: usize,
+    /// Baseline entries that have not been encountered for any of the analyzed files.
+    pub fixed: Vec<String>,
+}
+
+/// Removes all findings contained in the baseline from the records.
+///
+/// The outcome of each record is re-evaluated using the remaining findings: Records without any
+/// new finding pass, records with new warnings only are reported as warnings. Failures without
+/// any diagnostic, e.g., if `clang-tidy` crashed, are not affected by the baseline.
+pub fn apply(
+    baseline: Baseline,
+    records: Vec<report::Record>,
+    roots: &[&path::Path],
+) -> (Vec<report::Record>, Applied) {
+    let model = baseline.model;
+    let mut budget: HashMap<Key, usize> = model
+        .findings
+        .iter()
+        .map(|f| (f.key.clone(), f.count))
+        .collect();
+
+    let mut keys = Keys::new();
+    // diagnostics that have already been evaluated, mapped to whether they are known
+    let mut seen = HashMap::new();
+    let mut analyzed = HashSet::new();
+    let mut suppressed = 0usize;
+
+    let records = records
+        .into_iter()
+        .map(|mut record| {
+            let root = roots[record.config];
+            if let Some(file) = cli::utils::relative_to(&record.path, root) {
+                analyzed.insert(file.to_string_lossy().replace('\\', "/"));
+            }
+
+            let output = match &record.result {
+                cmd::RunResult::Err(output) if !output.diagnostics.is_empty() => output,
+                cmd::RunResult::Warn(output) => output,
+                _ => return record,
+            };
+
+            let mut known = 0usize;
+            let remaining: Vec<_> = output
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| {
+                    if !is_finding(diagnostic) {
+                        return true;
+                    }
+                    let is_known =
+                        *seen.entry(identity(diagnostic)).or_insert_with(|| {
+                            match budget.get_mut(&keys.key(diagnostic, &record.path, root)) {
+                                Some(count) if *count > 0 => {
+                                    *count -= 1;
+                                    true
+                                }
+                                _ => false,
+                            }
+                        });
+                    if is_known {
+                        known += 1;
+                    }
+                    !is_known
+                })
+                .cloned()
+                .collect();
+
+            if known == 0 {
+                return record;
+            }
+            suppressed += known;
+
+            let has = |severity| remaining.iter().any(|d| d.severity == severity);
+            let msg = format!(
+                "{known} finding(s) suppressed by baseline\n---\n{}",
+                remaining
+                    .iter()
+                    .filter(|d| is_finding(d))
+                    .map(|d| format!("{d}\n"))
+                    .collect::<String>()
+            );
+            let output = cmd::Output {
+                msg,
+                diagnostics: remaining.clone(),
+            };
+
+            record.result = if has(cmd::Severity::Error) {
+                cmd::RunResult::Err(output)
+            } else if has(cmd::Severity::Warning) {
+                cmd::RunResult::Warn(output)
+            } else {
+                cmd::RunResult::Ok
+            };
+            record
+        })
+        .collect();
+
+    let fixed = model
+        .findings
+        .iter()
+        .filter(|f| analyzed.contains(&f.key.file))
+        .filter_map(|f| match budget.get(&f.key) {
+            Some(count) if *count > 0 => Some(format!(
+                "{}: {} [{}]{}",
+                f.key.file,
+                f.message,
+                f.key.check,
+                if *count > 1 {
+                    format!(" ({count} occurrences)")
+                } else {
+                    "".to_string()
+                }
+            )),
+            _ => None,
+        })
+        .collect();
+
+    (records, Applied { suppressed, fixed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(line: u32, message: &str) -> cmd::Diagnostic {
+        cmd::Diagnostic {
+            location: Some(cmd::Location {
+                file: "/does/not/exist/main.c".into(),
+                line,
+                column: 1,
+            }),
+            severity: cmd::Severity::Warning,
+            message: message.to_string(),
+            check: Some("some-check".to_string()),
+            warning_as_error: false,
+            notes: vec![],
+            fixits: vec![],
+        }
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_key_is_line_insensitive() {
+        let mut keys = Keys::new();
+        let root = path::Path::new("/does/not");
+        let path = path::Path::new("/does/not/exist/main.c");
+
+        let a = keys.key(&diagnostic(1, "some message"), path, root);
+        let b = keys.key(&diagnostic(10, "some message"), path, root);
+        let c = keys.key(&diagnostic(1, "other message"), path, root);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!("exist/main.c", a.file);
+        assert_eq!(16, a.fingerprint.len());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    fs, path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{cmd, compdb, includes};
+
+/// Version of the cache entries, incremented for incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    format_version: u32,
+    outcome: Outcome,
+    msg: String,
+    diagnostics: Vec<cmd::Diagnostic>,
+}
+
+/// Hashes a sequence of byte strings, each part is terminated by a 0 byte.
+struct Hasher(Sha256);
+
+impl Hasher {
+    fn new() -> Hasher {
+        Hasher(Sha256::new())
+    }
+
+    fn part(&mut self, part: impl AsRef<[u8]>) -> &mut Hasher {
+        self.0.update(part.as_ref());
+        self.0.update([0u8]);
+        self
+    }
+
+    fn file(&mut self, file: &path::Path) -> &mut Hasher {
+        self.part(file.to_string_lossy().as_bytes());
+        // a missing file is distinguished from an empty file
+        match fs::read(file) {
+            Ok(content) => self.part("+").part(content),
+            Err(_) => self.part("-"),
+        }
+    }
+
+    fn finish(&mut self) -> String {
+        self.0
+            .clone()
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// Cache for the outcome of `clang-tidy` for single files.
+///
+/// An entry is identified by a hash of everything that affects the outcome: The content of the
+/// file and all files it (transitively) includes, its compile command, the `.clang-tidy` files
+/// in all parent directories of the file, the version of `clang-tidy`, and the options passed
+/// to `clang-tidy`. Changing any of those leads to a new entry, stale entries are never removed.
+pub struct Cache {
+    dir: path::PathBuf,
+    /// Hash of the parts of the key that are common to all files.
+    common: String,
+}
+
+impl Cache {
+    pub fn new(dir: &path::Path, command: &cmd::Runner, options: &[String]) -> eyre::Result<Cache> {
+        fs::create_dir_all(dir)
+            .wrap_err(format!(
+                "Failed to create cache directory '{}'",
+                dir.to_string_lossy()
+            ))
+            .suggestion("Please check the path and the permissions of the --cache-dir")?;
+
+        let mut hasher = Hasher::new();
+        hasher
+            .part(FORMAT_VERSION.to_string())
+            .part(command.get_path().to_string_lossy().as_bytes())
+            .part(command.get_version().unwrap_or_default());
+        for option in options.iter() {
+            hasher.part(option);
+        }
+
+        Ok(Cache {
+            dir: dir.to_path_buf(),
+            common: hasher.finish(),
+        })
+    }
+
+    /// Provides the key for the canonical `path` of a translation unit that is analyzed using
+    /// the `options` specific to this file.
+    pub fn key(
+        &self,
+        path: &path::Path,
+        options: &[String],
+        database: Option<&compdb::Database>,
+        scanner: &mut includes::Scanner,
+    ) -> String {
+        let mut hasher = Hasher::new();
+        hasher.part(&self.common).file(path);
+        for option in options.iter() {
+            hasher.part(option);
+        }
+
+        let entry = database.and_then(|database| database.entry(path));
+        let closure = match entry {
+            Some(entry) => {
+                hasher
+                    .part(entry.directory.to_string_lossy().as_bytes())
+                    .part(entry.arguments.join("\0"));
+                scanner.closure(entry)
+            }
+            None => {
+                hasher.part("-");
+                scanner.closure(&compdb::Entry {
+                    directory: path.parent().unwrap().to_path_buf(),
+                    file: path.to_path_buf(),
+                    arguments: vec![],
+                })
+            }
+        };
+
+        let mut closure: Vec<_> = closure.into_iter().collect();
+        closure.sort();
+        for file in closure.iter() {
+            hasher.file(file);
+        }
+
+        // clang-tidy uses the closest .clang-tidy file, which may inherit the configuration
+        // of .clang-tidy files in its parent directories
+        for dir in path.ancestors().skip(1) {
+            let file = dir.join(".clang-tidy");
+            if file.is_file() {
+                hasher.file(&file);
+            }
+        }
+        hasher.finish()
+    }
+
+    fn file(&self, key: &str) -> path::PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// Provides the cached result for the `key`, if any.
+    pub fn get(&self, key: &str) -> Option<cmd::RunResult> {
+        let content = fs::read_to_string(self.file(key)).ok()?;
+        let entry: Entry = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(err) => {
+                log::debug!("Ignoring invalid cache entry {key}: {err}");
+                return None;
+            }
+        };
+        if entry.format_version != FORMAT_VERSION {
+            return None;
+        }
+
+        let output = cmd::Output {
+            msg: entry.msg,
+            diagnostics: entry.diagnostics,
+        };
+        Some(match entry.outcome {
+            Outcome::Ok => cmd::RunResult::Ok,
+            Outcome::Warning => cmd::RunResult::Warn(output),
+            Outcome::Error => cmd::RunResult::Err(output),
+        })
+    }
+
+    /// Stores the `result` for the `key`. Failures without any diagnostic, e.g., if `clang-tidy`
+    /// could not be executed or crashed, and timeouts are not cached.
+    pub fn put(&self, key: &str, result: &cmd::RunResult) {
+        let (outcome, output) = match result {
+            cmd::RunResult::Ok => (Outcome::Ok, None),
+            cmd::RunResult::Warn(output) => (Outcome::Warning, Some(output)),
+            cmd::RunResult::Err(output) if !output.diagnostics.is_empty() => {
+                (Outcome::Error, Some(output))
+            }
+            cmd::RunResult::Err(_) | cmd::RunResult::Timeout(_) => return,
+        };
+
+        let entry = Entry {
+            format_version: FORMAT_VERSION,
+            outcome,
+            msg: output.map(|o| o.msg.clone()).unwrap_or_default(),
+            diagnostics: output.map(|o| o.diagnostics.clone()).unwrap_or_default(),
+        };
+
+        // entries are written to a temporary file first such that concurrent runs never
+        // encounter partially written entries. The counter keeps the names unique if the same
+        // entry is written by multiple threads, e.g., for files listed in several configurations
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let file = self.file(key);
+        let tmp = self.dir.join(format!(
+            "{key}.{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = fs::write(&tmp, serde_json::to_string(&entry).unwrap())
+            .and_then(|_| fs::rename(&tmp, &file));
+
+        if let Err(err) = written {
+            log::debug!(
+                "Failed to write cache entry '{}': {err}",
+                file.to_string_lossy()
+            );
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hasher_parts() {
+        // parts are separated such that moving bytes between parts changes the hash
+        assert_ne!(
+            Hasher::new().part("ab").part("c").finish(),
+            Hasher::new().part("a").part("bc").finish()
+        );
+        assert_eq!(64, Hasher::new().finish().len());
+    }
+
+    #[test]
+    fn test_key_get_put() {
+        let dir = std::env::temp_dir().join(format!("run-clang-tidy-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let source = dir.join("src/a.c");
+        let header = dir.join("src/a.h");
+        let tidy_file = dir.join(".clang-tidy");
+        fs::write(&source, "#include \"a.h\"\n").unwrap();
+        fs::write(&header, "").unwrap();
+        fs::write(&tidy_file, "Checks: '-*'\n").unwrap();
+
+        let cache = Cache::new(&dir.join("cache"), &cmd::Runner::new("clang-tidy"), &[]).unwrap();
+        let key = || cache.key(&source, &[], None, &mut includes::Scanner::new());
+        let initial = key();
+        assert_eq!(initial, key());
+        assert!(cache.get(&initial).is_none());
+
+        // results are replayed with their diagnostics
+        let output = cmd::Output {
+            msg: "warnings encountered".to_string(),
+            diagnostics: vec![cmd::Diagnostic {
+                location: None,
+                severity: cmd::Severity::Warning,
+                message: "message".to_string(),
+                check: Some("some-check".to_string()),
+                warning_as_error: false,
+                notes: vec![],
+                fixits: vec![],
+            }],
+        };
+        cache.put(&initial, &cmd::RunResult::Warn(output.clone()));
+        match cache.get(&initial) {
+            Some(cmd::RunResult::Warn(cached)) => {
+                assert_eq!(output.msg, cached.msg);
+                assert_eq!(output.diagnostics, cached.diagnostics);
+            }
+            result => panic!("Unexpected cached result {result:?}"),
+        }
+
+        // changing the source, an included header or the tidy file leads to a miss
+        for file in [&source, &header, &tidy_file] {
+            let content = fs::read_to_string(file).unwrap();
+            fs::write(file, format!("{content}// changed\n")).unwrap();
+            assert_ne!(initial, key());
+            fs::write(file, content).unwrap();
+            assert_eq!(initial, key());
+        }
+        let options = ["--checks=-*".to_string()];
+        assert_ne!(
+            initial,
+            cache.key(&source, &options, None, &mut includes::Scanner::new())
+        );
+
+        // failures without diagnostics and timeouts are not cached
+        let failure = cmd::Output {
+            msg: "failed".to_string(),
+            diagnostics: vec![],
+        };
+        cache.put("failure", &cmd::RunResult::Err(failure.clone()));
+        cache.put("timeout", &cmd::RunResult::Timeout(output));
+        assert!(cache.get("failure").is_none());
+        assert!(cache.get("timeout").is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
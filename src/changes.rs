@@ -0,0 +1,77 @@
+use std::{collections::HashSet, path, process};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+fn git(dir: &path::Path, args: &[&str]) -> eyre::Result<String> {
+    let output = process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .wrap_err(format!("Failed to execute 'git {}'", args.join(" ")))
+        .suggestion("Please make sure that 'git' is installed and in your search path")?;
+
+    if !output.status.success() {
+        return Err(eyre!(
+            "'git {}' failed with {}:\n{}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Provides the canonical paths of all files that have been changed since the revision `rev`,
+/// including uncommitted changes and untracked files of the working tree. Deleted files are
+/// not contained in the result.
+///
+/// The git repository is determined by the directory `dir`.
+pub fn since(dir: &path::Path, rev: &str) -> eyre::Result<HashSet<path::PathBuf>> {
+    // the revision is passed to git as is and must therefore not be interpreted as an option
+    if rev.starts_with('-') {
+        return Err(eyre!("Invalid git revision '{rev}'"))
+            .suggestion("Please provide a revision that does not start with '-'");
+    }
+
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+        .wrap_err(format!(
+            "Failed to determine the git repository of '{}'",
+            dir.to_string_lossy()
+        ))
+        .suggestion("The option --changed-since can only be used within a git repository")?;
+    let toplevel = path::PathBuf::from(toplevel.trim_end());
+
+    // both commands list paths relative to the top-level directory of the repository
+    let changed = git(dir, &["diff", "--name-only", "-z", rev, "--"])
+        .wrap_err(format!("Failed to list the files changed since '{rev}'"))
+        .suggestion("Please make sure that the provided revision exists")?;
+    let untracked = git(
+        dir,
+        &[
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "--full-name",
+            "-z",
+        ],
+    )
+    .wrap_err("Failed to list untracked files")?;
+
+    let files = changed
+        .split('\0')
+        .chain(untracked.split('\0'))
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| toplevel.join(name).canonicalize().ok())
+        .collect::<HashSet<_>>();
+
+    log::debug!(
+        "files changed since '{rev}'\n{}",
+        files
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+    Ok(files)
+}
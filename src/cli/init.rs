@@ -0,0 +1,219 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::Serialize;
+
+use super::{utils, JsonModel};
+
+/// Name of the configuration file created by the `init` subcommand.
+const FILE_NAME: &str = "run-clang-tidy.json";
+
+/// Extensions of the translation units that are added to the field "paths".
+const SOURCE_EXTENSIONS: [&str; 7] = ["c", "cc", "cpp", "cxx", "c++", "m", "mm"];
+
+/// Starter configuration, serialized in the order of the fields.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Starter {
+    paths: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tidy_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_root: Option<String>,
+}
+
+/// Files found in the tree of the configuration file, relative to its root.
+#[derive(Default)]
+struct Survey {
+    compile_commands: Vec<path::PathBuf>,
+    tidy_files: Vec<path::PathBuf>,
+    sources: Vec<path::PathBuf>,
+}
+
+impl Survey {
+    /// Recursively collects the files in `dir`, hidden files and folders are skipped just like
+    /// the default "filterPre" does.
+    fn collect(&mut self, root: &path::Path, dir: &path::Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            log::debug!("Skipping unreadable folder {}", dir.to_string_lossy());
+            return;
+        };
+
+        let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(fs::DirEntry::file_name);
+
+        for entry in entries {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = path.strip_prefix(root).unwrap().to_path_buf();
+
+            if name == ".clang-tidy" {
+                self.tidy_files.push(rel);
+                continue;
+            }
+            if name.starts_with('.') {
+                continue;
+            }
+
+            // symbolic links to folders are not followed to avoid cycles
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => self.collect(root, &path),
+                Ok(_) if name == "compile_commands.json" => self.compile_commands.push(rel),
+                Ok(_) if is_source(&path) => self.sources.push(rel),
+                _ => (),
+            }
+        }
+    }
+}
+
+fn is_source(path: &path::Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(|ext| SOURCE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Relative path using forward slashes, such that the configuration is portable.
+fn portable(path: &path::Path) -> String {
+    let parts: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    match parts.is_empty() {
+        true => ".".to_string(),
+        false => format!("./{}", parts.join("/")),
+    }
+}
+
+/// Picks the shallowest of the `candidates`, ties are resolved alphabetically.
+fn closest(candidates: &[path::PathBuf], what: &str) -> Option<path::PathBuf> {
+    let picked = candidates
+        .iter()
+        .min_by_key(|path| (path.components().count(), path.to_path_buf()))?;
+
+    for other in candidates.iter().filter(|path| *path != picked) {
+        log::info!("Ignoring alternative {what} {}", portable(other));
+    }
+    Some(picked.clone())
+}
+
+/// Provides the globs for all sources, one glob per top-level folder and extension. Sources
+/// within the top-level folder of the `build_root` are skipped since they are typically generated.
+fn source_globs(sources: &[path::PathBuf], build_root: Option<&path::Path>) -> Vec<String> {
+    let mut globs: BTreeMap<Option<path::PathBuf>, BTreeSet<String>> = BTreeMap::new();
+    let skipped = build_root.and_then(|build_root| build_root.components().next());
+
+    for source in sources.iter() {
+        if source.components().count() > 1 && source.components().next() == skipped {
+            continue;
+        }
+        let ext = source.extension().unwrap().to_string_lossy().to_string();
+        let folder = match source.components().count() {
+            1 => None,
+            _ => source.components().next().map(|c| c.as_os_str().into()),
+        };
+        globs.entry(folder).or_default().insert(ext);
+    }
+
+    globs
+        .into_iter()
+        .flat_map(|(folder, extensions)| {
+            extensions.into_iter().map(move |ext| match &folder {
+                None => format!("./*.{ext}"),
+                Some(folder) => format!("{}/**/*.{ext}", portable(folder)),
+            })
+        })
+        .collect()
+}
+
+/// Inspects the tree of the directory `dir` and writes a starter configuration to `dir`.
+pub fn run(dir: &path::Path, force: bool) -> eyre::Result<path::PathBuf> {
+    let dir = utils::dir_or_err(dir)
+        .and_then(|dir| Ok(dir.canonicalize()?))
+        .wrap_err("Invalid parameter for <DIR>")?;
+
+    let file = dir.join(FILE_NAME);
+    if file.exists() && !force {
+        return Err(eyre!(
+            "Configuration file '{}' already exists",
+            file.to_string_lossy()
+        ))
+        .suggestion("Please use --force to overwrite the existing file.");
+    }
+
+    let mut survey = Survey::default();
+    survey.collect(&dir, &dir);
+
+    let build_root = closest(&survey.compile_commands, "compile_commands.json")
+        .map(|file| file.parent().unwrap().to_path_buf());
+    let tidy_file = closest(&survey.tidy_files, ".clang-tidy file");
+    let paths = source_globs(&survey.sources, build_root.as_deref());
+
+    if build_root.is_none() {
+        log::warn!(
+            "No compile_commands.json found, please add the field 'buildRoot' or use --build-root"
+        );
+    }
+    if paths.is_empty() {
+        log::warn!("No source files found, please add the patterns for your sources to 'paths'");
+    }
+
+    let starter = Starter {
+        paths,
+        tidy_file: tidy_file.as_deref().map(portable),
+        build_root: build_root.as_deref().map(portable),
+    };
+
+    let content = serde_json::to_string_pretty(&starter).unwrap();
+    // the generated configuration must be accepted when loading it
+    serde_json::from_str::<JsonModel>(&content)
+        .wrap_err("Failed to generate a valid configuration")?;
+
+    fs::write(&file, content + "\n").wrap_err(format!(
+        "Failed to write configuration file '{}'",
+        file.to_string_lossy()
+    ))?;
+
+    log::info!("Created configuration file {}", file.to_string_lossy());
+    for path in starter.paths.iter() {
+        log::info!("  paths: {path}");
+    }
+    if let Some(tidy_file) = &starter.tidy_file {
+        log::info!("  tidyFile: {tidy_file}");
+    }
+    if let Some(build_root) = &starter.build_root {
+        log::info!("  buildRoot: {build_root}");
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_globs() {
+        let sources: Vec<path::PathBuf> = [
+            "main.c",
+            "src/a/a.c",
+            "src/b/b.cpp",
+            "src/b/c.c",
+            "_bld/gen/gen.c",
+            "_bld/out/out.c",
+        ]
+        .iter()
+        .map(path::PathBuf::from)
+        .collect();
+
+        assert_eq!(
+            vec!["./*.c", "./src/**/*.c", "./src/**/*.cpp"],
+            source_globs(&sources, Some(path::Path::new("_bld/out")))
+        );
+        // compile commands in the root folder do not exclude any sources
+        assert_eq!(4, source_globs(&sources, Some(path::Path::new(""))).len());
+    }
+}
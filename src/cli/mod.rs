@@ -1,6 +1,7 @@
-use std::{path, process};
+use std::{collections::BTreeMap, path, process};
 
 mod handlers;
+mod init;
 mod logging;
 pub mod utils;
 
@@ -13,11 +14,30 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")] // removed: deny_unknown_fields
 pub struct JsonModel {
+    /// Optional path to a base configuration file, relative to this file.
+    /// All fields of the base configuration are inherited unless they are specified in this file.
+    /// The lists "paths", "filterPre", "filterPost", "extraArgs" and "extraArgsBefore" are
+    /// appended to the lists of the base configuration, unless listed in "replace". Relative paths
+    /// are resolved against the directory of the file they are specified in.
+    pub extends: Option<path::PathBuf>,
+    /// Optional list of the lists that replace the lists of the base configuration instead of
+    /// being appended, e.g., ["paths"]. Listed fields that are not specified in this file are not
+    /// inherited from the base configuration.
+    pub replace: Option<Vec<ListField>>,
     /// List of paths and/or globs.
     /// This list may contain paths or shell-style globs to define the files that should be
     /// filtered. Paths or globs that resolve to folders will be silently ignored. Any path
-    /// contained in this list must be specified relative to the configuration file.
-    pub paths: Vec<String>,
+    /// contained in this list must be specified relative to the configuration file. This field is
+    /// optional if the files are taken from the compile commands only, see "source".
+    /// Entries can also be groups of paths with their own options, e.g., stricter checks for
+    /// some of the paths. For files matched by multiple groups, the last group takes precedence.
+    pub paths: Option<Vec<PathEntry>>,
+    /// Optional source of the files to analyze, defaults to "paths".
+    /// For "compileCommands", all translation units listed in the `compile_commands.json` of the
+    /// build root are analyzed instead of the files matching "paths". For "both", the files of
+    /// both sources are analyzed. The filters "filterPre" and "filterPost" are applied to all
+    /// files, independent of their source.
+    pub source: Option<Source>,
     /// Optional list of globs used for efficiently pre-filtering paths.
     /// In contrast to the post-filter, searching will completely skip all paths and its siblings
     /// for any match with any pattern. E.g., [".git"] will skip all ".git" folders completely.
@@ -34,15 +54,36 @@ pub struct JsonModel {
     /// is provided by neither this field nor the command-line option, `clang-tidy` will perform a
     /// search for the `compile_commands.json` through all parent paths of the file to analyze.
     pub tidy_file: Option<path::PathBuf>,
-    // TODO: allow this config to be skipped for clang-tidy >= 12.0.0
-    /// Optional path where the `.clang-tidy` file should be copied to while executing.
+    /// Optional inline `clang-tidy` configuration, passed to `clang-tidy` using `--config`.
+    /// This field replaces any `.clang-tidy` file and can therefore not be combined with the
+    /// field "tidyFile".
+    pub tidy_config: Option<TidyConfig>,
+    /// Optional path where the `.clang-tidy` file should be copied to while executing. This field
+    /// is only used for `clang-tidy` versions below 12.0.0, which do not support the option
+    /// `--config-file`.
     pub tidy_root: Option<path::PathBuf>,
     /// Optional path to the folder that contains the `compile-commands.json` (can be specified
     /// via --build-root).
     pub build_root: Option<path::PathBuf>,
     /// Optional path to the `clang-tidy` executable or command name
     pub command: Option<path::PathBuf>,
-    // TODO: allow to specify additional options
+    /// Optional list of additional compiler arguments, passed to `clang-tidy` as `--extra-arg`.
+    pub extra_args: Option<Vec<String>>,
+    /// Optional list of additional compiler arguments that are prepended to the compiler command
+    /// line, passed to `clang-tidy` as `--extra-arg-before`.
+    pub extra_args_before: Option<Vec<String>>,
+    /// Optional comma-separated list of globs for enabling and disabling checks, passed to
+    /// `clang-tidy` as `--checks`. The checks are added to the checks of the tidy file.
+    pub checks: Option<String>,
+    /// Optional regular expression matching the headers to output diagnostics from, passed to
+    /// `clang-tidy` as `--header-filter`.
+    pub header_filter: Option<String>,
+    /// Optional comma-separated list of globs for checks whose warnings are treated as errors,
+    /// passed to `clang-tidy` as `--warnings-as-errors`.
+    pub warnings_as_errors: Option<String>,
+    /// Optional timeout in seconds for analyzing a single file. `clang-tidy` is terminated if it
+    /// does not finish within the timeout and the file is reported as failed.
+    pub timeout: Option<u64>,
     #[serde(skip)]
     /// Parent directory of the Json file, used to resolve paths specified within
     pub root: path::PathBuf,
@@ -51,17 +92,220 @@ pub struct JsonModel {
     pub name: String,
 }
 
+/// Workspace file, listing the configuration files that are analyzed within a single run.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct WorkspaceModel {
+    /// Paths to the configuration files of all members, relative to the workspace file.
+    members: Vec<path::PathBuf>,
+}
+
+/// Names of the configuration files that are used if no <JSON> is provided, by precedence.
+const CONFIG_NAMES: [&str; 8] = [
+    "run-clang-tidy.json",
+    ".run-clang-tidy.json",
+    "run-clang-tidy.toml",
+    ".run-clang-tidy.toml",
+    "run-clang-tidy.yaml",
+    ".run-clang-tidy.yaml",
+    "run-clang-tidy.yml",
+    ".run-clang-tidy.yml",
+];
+
+/// Format of a configuration file, determined by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    fn of(path: &path::Path) -> eyre::Result<Format> {
+        let ext = path
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .map(str::to_lowercase);
+
+        match ext.as_deref() {
+            Some("json") => Ok(Format::Json),
+            Some("toml") => Ok(Format::Toml),
+            Some("yaml" | "yml") => Ok(Format::Yaml),
+            _ => Err(eyre!(
+                "Expected file with extension 'json', 'toml', 'yaml' or 'yml', got file '{}'",
+                path.to_string_lossy()
+            )),
+        }
+    }
+
+    /// Reads the configuration file at `path` as JSON value, such that all formats share the
+    /// same schema.
+    fn read(path: &path::Path) -> eyre::Result<serde_json::Value> {
+        let format = Format::of(path)?;
+        let name = path.to_string_lossy();
+        let content = std::fs::read_to_string(path).wrap_err(format!(
+            "Failed to open provided configuration file '{name}'"
+        ))?;
+
+        let value = match format {
+            Format::Json => serde_json::from_str(&content).map_err(|err| eyre!(err)),
+            Format::Toml => toml::from_str(&content).map_err(|err| eyre!(err)),
+            Format::Yaml => serde_norway::from_str(&content).map_err(|err| eyre!(err)),
+        };
+        value
+            .wrap_err(format!("Validation failed for '{name}'"))
+            .suggestion(format!(
+                "Please make sure that '{name}' is a valid .{} file.",
+                format!("{format:?}").to_lowercase()
+            ))
+    }
+}
+
+/// Entry of the field "paths".
+#[derive(Deserialize, Debug, Clone, JsonSchema)]
+#[serde(untagged)]
+pub enum PathEntry {
+    /// Path or glob.
+    Glob(String),
+    /// Group of paths or globs with their own options.
+    Group(PathGroup),
+}
+
+/// Group of paths or globs with their own options.
+///
+/// The options "checks" and "warningsAsErrors" replace the corresponding options of the
+/// configuration file, whereas "extraArgs" are added to the configured "extraArgs".
+#[derive(Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PathGroup {
+    /// List of paths and/or globs, relative to the configuration file.
+    pub paths: Vec<String>,
+    /// Optional comma-separated list of globs for enabling and disabling checks.
+    pub checks: Option<String>,
+    /// Optional list of additional compiler arguments.
+    pub extra_args: Option<Vec<String>>,
+    /// Optional path to a `.clang-tidy` file, relative to the configuration file. Requires
+    /// `clang-tidy` version 12.0.0 or higher.
+    pub tidy_file: Option<path::PathBuf>,
+    /// Optional comma-separated list of globs for checks whose warnings are treated as errors.
+    pub warnings_as_errors: Option<String>,
+}
+
+/// Inline configuration for `clang-tidy`, equivalent to the contents of a `.clang-tidy` file.
+#[derive(Deserialize, Debug, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TidyConfig {
+    /// List of globs for enabling and disabling checks, e.g., ["-*", "bugprone-*"].
+    pub checks: Option<Vec<String>>,
+    /// Options of the checks, e.g., {"readability-function-size.LineThreshold": 100}.
+    pub check_options: Option<BTreeMap<String, serde_json::Value>>,
+    /// List of globs for checks whose warnings are treated as errors.
+    pub warnings_as_errors: Option<Vec<String>>,
+    /// Regular expression matching the headers to output diagnostics from.
+    pub header_filter_regex: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+    /// Files matching the field "paths".
+    Paths,
+    /// Translation units of the `compile_commands.json` in the build root.
+    CompileCommands,
+    /// Files of both sources.
+    Both,
+}
+
+/// Lists of a configuration that are appended to the lists of its base configuration.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ListField {
+    Paths,
+    FilterPre,
+    FilterPost,
+    ExtraArgs,
+    ExtraArgsBefore,
+}
+
 // goal: have compatible .json configuration files for clang-format and clang-tidy
-// it should be possible to specify all command line options and non-unit relative paths
-// using the command line, such that they can be set using ENV variables
+// all fields of the configuration can be overridden using the command line or the corresponding
+// RUN_CLANG_TIDY_* environment variables, see `Builder::args`
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Static Analysis Results Interchange Format (SARIF) 2.1.0
+    Sarif,
+    /// JUnit XML with one test case per analyzed file
+    Junit,
+    /// JSON summary of the run
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+/// Handling of files without an entry in the `compile_commands.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingCommands {
+    Skip,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub format: ReportFormat,
+    /// Path of the report file, relative to the current working directory.
+    pub file: path::PathBuf,
+    /// Report warnings as failures instead of skipped test cases (JUnit only).
+    pub warnings_as_failures: bool,
+}
+
+#[derive(Debug)]
+pub struct Baseline {
+    /// Path to the baseline file, relative to the current working directory.
+    pub file: path::PathBuf,
+    /// Record all findings in the baseline file instead of suppressing known findings.
+    pub update: bool,
+}
 
 #[derive(Debug)]
 pub struct Data {
-    /// Json input data
-    pub json: JsonModel,
+    /// Json input data, one entry for each configuration file
+    pub jsons: Vec<JsonModel>,
+    /// Common parent directory of all configuration files, used to create relative paths
+    pub root: path::PathBuf,
+    /// Lossy name of the configuration or workspace file(s)
+    pub name: String,
     /// Command-line override for the tidy file
     pub tidy_file: Option<path::PathBuf>,
-    // TODO: add tidy_root override
+    /// Command-line override for the tidy root folder
+    pub tidy_root: Option<path::PathBuf>,
+    /// Command-line override for the inline tidy configuration
+    pub tidy_config: Option<TidyConfig>,
+    /// Command-line override for the paths, relative to the current working directory
+    pub paths: Option<Vec<String>>,
+    /// Command-line override for the source of the files
+    pub source: Option<Source>,
+    /// Command-line override for the pre-filter
+    pub filter_pre: Option<Vec<String>>,
+    /// Command-line override for the post-filter
+    pub filter_post: Option<Vec<String>>,
+    /// Command-line override for the additional compiler arguments
+    pub extra_args: Option<Vec<String>>,
+    /// Command-line override for the additional compiler arguments prepended to the command line
+    pub extra_args_before: Option<Vec<String>>,
+    /// Command-line override for the checks
+    pub checks: Option<String>,
+    /// Command-line override for the header filter
+    pub header_filter: Option<String>,
+    /// Command-line override for the checks whose warnings are treated as errors
+    pub warnings_as_errors: Option<String>,
+    /// Command-line override for the timeout in seconds for analyzing a single file
+    pub timeout: Option<u64>,
     /// Command-line override for the build root folder
     pub build_root: Option<path::PathBuf>,
     /// Command-line override for the clang-tidy executable
@@ -73,8 +317,32 @@ pub struct Data {
     pub ignore_warn: bool,
     /// Suppress all logging.
     pub quiet: bool,
-    /// Run with -fix argument.
+    /// Apply the fixes of all files once all files have been analyzed.
     pub fix: bool,
+    /// Optional directory to export the fixes to instead of applying them.
+    pub export_fixes: Option<path::PathBuf>,
+    /// Show the fixes as unified diff instead of applying them.
+    pub dry_run: bool,
+    /// Optional file to write the unified diff of a dry run to, instead of stdout.
+    pub patch_file: Option<path::PathBuf>,
+    /// Optional report file to write all findings to.
+    pub report: Option<Report>,
+    /// Format of the output that is printed to stdout.
+    pub output: OutputFormat,
+    /// Optional baseline of known findings.
+    pub baseline: Option<Baseline>,
+    /// Only analyze files changed since the provided git revision.
+    pub changed_since: Option<String>,
+    /// Optional directory for caching the results of unchanged files.
+    pub cache_dir: Option<path::PathBuf>,
+    /// Optional handling of files without compile command, files are not checked if not set.
+    pub missing_commands: Option<MissingCommands>,
+    /// Optional number of failed files after which no further files are analyzed.
+    pub max_failures: Option<usize>,
+    /// Additional arguments for `clang-tidy`, passed after `--` on the command line.
+    pub tidy_args: Vec<String>,
+    /// Only validate the configuration and list the files, without executing `clang-tidy`.
+    pub check: bool,
 }
 
 pub struct Builder {
@@ -82,16 +350,21 @@ pub struct Builder {
 }
 
 impl Builder {
-    fn app() -> clap::Command {
-        clap::Command::new(crate_name!())
-            .arg_required_else_help(true)
-            .version(crate_version!())
-            .author(crate_authors!())
-            .about(crate_description!())
+    /// Arguments for executing `clang-tidy`, shared by all commands that perform a run.
+    fn args(cmd: clap::Command) -> clap::Command {
+        cmd
             .arg(
-                arg!(<JSON>)
-                    .help("Path/configuration as .json")
-                    .value_parser(clap::value_parser!(std::path::PathBuf)),
+                arg!([JSON])
+                    .help(
+                        "Path/configuration as .json, .toml or .yaml. Multiple configurations, \
+                         or a workspace file listing its \"members\", are analyzed within a \
+                         single run. If omitted, the closest run-clang-tidy.json (or .toml, \
+                         .yaml, optionally prefixed with a '.') in the current directory or \
+                         any of its parents is used.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .num_args(1..)
+                    .action(clap::ArgAction::Append),
             )
             .arg(
                 arg!(-t --tidy ... "Optional path to the .clang-tidy configuration file. \
@@ -99,6 +372,7 @@ impl Builder {
                                     `clang-tidy` will attempt a search for the compile commands \
                                     through all parent paths of the file that is being analyzed.")
                 .value_parser(clap::value_parser!(std::path::PathBuf))
+                .env("RUN_CLANG_TIDY_TIDY_FILE")
                 .required(false)
                 .action(clap::ArgAction::Set),
             )
@@ -112,6 +386,7 @@ impl Builder {
                          configuration.",
                     )
                     .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .env("RUN_CLANG_TIDY_BUILD_ROOT")
                     .action(clap::ArgAction::Set)
                     .required(false),
             )
@@ -119,9 +394,151 @@ impl Builder {
                 arg!(-c --command ... "Optional path to executable or clang-tidy command. \
                                        Overrides <JSON> configuration, defaults to `clang-tidy`")
                 .value_parser(clap::value_parser!(std::path::PathBuf))
+                .env("RUN_CLANG_TIDY_COMMAND")
                 .required(false)
                 .action(clap::ArgAction::Set),
             )
+            .arg(
+                Arg::new("tidy-root")
+                    .long("tidy-root")
+                    .help(
+                        "Optional path to the folder the tidy file is copied to for clang-tidy \
+                         versions below 12.0.0. Overrides <JSON> configuration.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .env("RUN_CLANG_TIDY_TIDY_ROOT")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("tidy-config")
+                    .long("tidy-config")
+                    .value_name("JSON")
+                    .help(
+                        "Optional inline tidy configuration in the format of the field \
+                         'tidyConfig', e.g., '{\"checks\": [\"-*\", \"bugprone-*\"]}'. \
+                         Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_TIDY_CONFIG")
+                    .conflicts_with("tidy")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("paths")
+                    .long("paths")
+                    .value_name("GLOB")
+                    .help(
+                        "Paths or globs of the files to analyze, relative to the current \
+                         working directory. Can be specified multiple times or as a ';' separated \
+                         list. Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_PATHS")
+                    .value_delimiter(';')
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("source")
+                    .long("source")
+                    .help("Source of the files to analyze. Overrides <JSON> configuration.")
+                    .value_parser(["paths", "compileCommands", "both"])
+                    .env("RUN_CLANG_TIDY_SOURCE")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("filter-pre")
+                    .long("filter-pre")
+                    .value_name("GLOB")
+                    .help(
+                        "Globs for pre-filtering paths. Can be specified multiple times or as a \
+                         ';' separated list, an empty value disables pre-filtering. Overrides \
+                         <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_FILTER_PRE")
+                    .value_delimiter(';')
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("filter-post")
+                    .long("filter-post")
+                    .value_name("GLOB")
+                    .help(
+                        "Globs for post-filtering paths. Can be specified multiple times or as a \
+                         ';' separated list. Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_FILTER_POST")
+                    .value_delimiter(';')
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("extra-arg")
+                    .long("extra-arg")
+                    .value_name("ARG")
+                    .help(
+                        "Additional compiler argument, passed to clang-tidy as --extra-arg. Can \
+                         be specified multiple times or as a ';' separated list. Overrides \
+                         <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_EXTRA_ARGS")
+                    .value_delimiter(';')
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("extra-arg-before")
+                    .long("extra-arg-before")
+                    .value_name("ARG")
+                    .help(
+                        "Additional compiler argument, passed to clang-tidy as \
+                         --extra-arg-before. Can be specified multiple times or as a ';' \
+                         separated list. Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_EXTRA_ARGS_BEFORE")
+                    .value_delimiter(';')
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("checks")
+                    .long("checks")
+                    .help("Globs for enabling and disabling checks. Overrides <JSON> configuration.")
+                    .env("RUN_CLANG_TIDY_CHECKS")
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("header-filter")
+                    .long("header-filter")
+                    .value_name("REGEX")
+                    .help(
+                        "Regular expression matching the headers to output diagnostics from. \
+                         Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_HEADER_FILTER")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("warnings-as-errors")
+                    .long("warnings-as-errors")
+                    .value_name("CHECKS")
+                    .help(
+                        "Globs for checks whose warnings are treated as errors. Overrides \
+                         <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_WARNINGS_AS_ERRORS")
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .value_name("SECONDS")
+                    .help(
+                        "Timeout for analyzing a single file, clang-tidy is terminated if it \
+                         does not finish in time. Overrides <JSON> configuration.",
+                    )
+                    .env("RUN_CLANG_TIDY_TIMEOUT")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .action(clap::ArgAction::Set),
+            )
             .arg(
                 arg!(-j --jobs ... "Optional parameter to define the number of jobs to use. \
                                     If provided without value (e.g., '-j') all available logical \
@@ -131,7 +548,45 @@ impl Builder {
                 .action(clap::ArgAction::Set),
             )
             .arg(arg!(-v --verbose ... "Verbosity, use -vv... for verbose output.").global(true))
-            .arg(arg!(--fix "Fix findings, if possible. Executes clang-tidy with the -fix and -fix-errors options."))
+            .arg(arg!(--fix "Fix findings, if possible. The fixes of all files are applied once all files have been analyzed, conflicting fixes are skipped."))
+            .arg(
+                Arg::new("export-fixes")
+                    .long("export-fixes")
+                    .value_name("DIR")
+                    .help(
+                        "Export the fixes to the file fixes.yaml in <DIR> instead of applying \
+                         them. The fixes of all files are merged such that they can be applied \
+                         using clang-apply-replacements <DIR>.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .conflicts_with("fix")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .help(
+                        "Print the changes of --fix as unified diff instead of applying them, \
+                         no file is modified.",
+                    )
+                    .requires("fix")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("patch-file")
+                    .long("patch-file")
+                    .value_name("FILE")
+                    .help(
+                        "Write the unified diff of --dry-run to <FILE> instead of printing it. \
+                         The paths are relative to the current directory, such that the patch \
+                         can be applied using 'git apply <FILE>'. Required for --output=json, \
+                         since stdout only contains the JSON summary.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .requires("dry-run")
+                    .required_if_eq_all([("dry-run", "true"), ("output", "json")])
+                    .action(clap::ArgAction::Set),
+            )
             .arg(
                 arg!(-q --quiet "Suppress all output except for errors; overrides -v")
                     .action(clap::ArgAction::SetTrue),
@@ -142,11 +597,164 @@ impl Builder {
                     .action(clap::ArgAction::SetTrue)
                     .help("Suppress warnings; overrides -v"),
             )
+            .arg(
+                Arg::new("report-format")
+                    .long("report-format")
+                    .help("Format of the report that is written to --report-file.")
+                    .value_parser(["sarif", "junit", "json"])
+                    .requires("report-file")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("report-file")
+                    .long("report-file")
+                    .help("Path to the report file containing all findings, see --report-format.")
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .requires("report-format")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("junit-warnings")
+                    .long("junit-warnings")
+                    .help(
+                        "Test case result for files with warnings in JUnit reports, \
+                         defaults to 'skipped'.",
+                    )
+                    .value_parser(["skipped", "failure"])
+                    .requires("report-format")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .help(
+                        "Format of the output printed to stdout. For 'json', a summary of the \
+                         run is printed to stdout, whereas all log output is printed to stderr.",
+                    )
+                    .value_parser(["human", "json"])
+                    .default_value("human")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("baseline")
+                    .long("baseline")
+                    .help(
+                        "Path to a baseline file created with 'baseline update'. Findings \
+                         contained in the baseline are suppressed, such that the execution \
+                         only fails for new findings.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("changed-since")
+                    .long("changed-since")
+                    .value_name("REV")
+                    .help(
+                        "Only analyze files that have been changed since the git revision REV, \
+                         including uncommitted changes and untracked files. Translation units \
+                         that include a changed header are analyzed as well. Defaults to 'HEAD' \
+                         if provided without value, i.e., only the changes of the working tree \
+                         are considered.",
+                    )
+                    .num_args(0..=1)
+                    .default_missing_value("HEAD")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("cache-dir")
+                    .long("cache-dir")
+                    .help(
+                        "Optional directory for caching the results of clang-tidy. Files that \
+                         did not change since a previous run are skipped and their cached \
+                         results are replayed. Not used in combination with --fix or --export-fixes.",
+                    )
+                    .value_parser(clap::value_parser!(std::path::PathBuf))
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("missing-commands")
+                    .long("missing-commands")
+                    .help(
+                        "Handling of files without an entry in the compile_commands.json of \
+                         the build root, e.g., headers: 'skip' excludes such files from the \
+                         analysis, 'warn' lists them but still analyzes them, 'fail' aborts \
+                         before running clang-tidy. Files are not checked if not specified.",
+                    )
+                    .value_parser(["skip", "warn", "fail"])
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("fail-fast")
+                    .long("fail-fast")
+                    .help("Stop analyzing further files after the first failed file, same as --max-failures 1.")
+                    .conflicts_with("max-failures")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("max-failures")
+                    .long("max-failures")
+                    .value_name("N")
+                    .help(
+                        "Stop analyzing further files once <N> files failed. Files that are \
+                         already being analyzed are finished, the summary only contains the \
+                         analyzed files.",
+                    )
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("tidy-args")
+                    .value_name("ARGS")
+                    .help(
+                        "Additional arguments for clang-tidy, e.g., '-- --checks=-*,bugprone-*'. \
+                         The arguments are passed as-is after all other arguments.",
+                    )
+                    .num_args(1..)
+                    .last(true)
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Set),
+            )
+    }
+
+    pub(crate) fn app() -> clap::Command {
+        Builder::args(clap::Command::new(crate_name!()))
+            .version(crate_version!())
+            .author(crate_authors!())
+            .about(crate_description!())
             .subcommand_negates_reqs(true)
             .subcommand(
                 clap::Command::new("schema")
                     .about("Print the schema used for the <JSON> configuration file"),
             )
+            .subcommand(
+                clap::Command::new("init")
+                    .about(
+                        "Inspect the tree of <DIR> and write a starter configuration file \
+                         run-clang-tidy.json to <DIR>",
+                    )
+                    .arg(
+                        arg!([DIR] "Root directory of the configuration, defaults to the current \
+                            directory")
+                        .value_parser(clap::value_parser!(std::path::PathBuf)),
+                    )
+                    .arg(arg!(--force "Overwrite an existing configuration file")),
+            )
+            .subcommand(Builder::args(clap::Command::new("check-config").about(
+                "Validate the configuration and list the files to analyze without executing \
+                 clang-tidy",
+            )))
+            .subcommand(
+                clap::Command::new("baseline")
+                    .about("Manage the baseline of known findings")
+                    .subcommand_required(true)
+                    .subcommand(
+                        Builder::args(clap::Command::new("update").about(
+                            "Execute clang-tidy and record all findings in the --baseline file",
+                        ))
+                        .mut_arg("baseline", |arg| arg.required(true)),
+                    ),
+            )
     }
 
     pub fn build() -> Builder {
@@ -154,24 +762,96 @@ impl Builder {
         let builder = Builder {
             matches: cmd.get_matches(),
         };
-        logging::setup(&builder.matches);
+        logging::setup(builder.run_matches());
         builder
     }
 
+    /// Provides the matches of the (sub-)command that performs the run.
+    fn run_matches(&self) -> &clap::ArgMatches {
+        match self.matches.subcommand() {
+            Some(("baseline", baseline)) => match baseline.subcommand() {
+                Some(("update", update)) => update,
+                _ => unreachable!(),
+            },
+            Some(("check-config", check)) => check,
+            _ => &self.matches,
+        }
+    }
+
+    /// Searches the current directory and all of its parents for a configuration file with one
+    /// of the `CONFIG_NAMES`, the closest file is used.
+    fn discover_config() -> eyre::Result<std::path::PathBuf> {
+        let cwd = std::env::current_dir().wrap_err("Failed to determine the current directory")?;
+        let found = cwd.ancestors().find_map(|dir| {
+            CONFIG_NAMES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        });
+
+        match found {
+            Some(path) => {
+                log::info!("Using configuration file {}", path.to_string_lossy());
+                Ok(path)
+            }
+            None => Err(eyre!(
+                "No configuration file found in '{}' or any of its parent directories",
+                cwd.to_string_lossy()
+            ))
+            .wrap_err("Missing parameter <JSON>")
+            .suggestion(format!(
+                "Please provide the path to the configuration file or create one of the files {}.",
+                CONFIG_NAMES.map(|name| format!("'{name}'")).join(", ")
+            )),
+        }
+    }
+
     pub fn parse(self) -> eyre::Result<Data> {
         if self.matches.subcommand_matches("schema").is_some() {
             println!("{}", JsonModel::schema(),);
             process::exit(0);
         }
+        if let Some(matches) = self.matches.subcommand_matches("init") {
+            let dir = matches
+                .get_one::<std::path::PathBuf>("DIR")
+                .cloned()
+                .unwrap_or_else(|| std::path::PathBuf::from("."));
+            init::run(&dir, matches.get_flag("force"))?;
+            process::exit(0);
+        }
+        let matches = self.run_matches();
 
-        let json_path = self.path_for_key("JSON", true)?;
-        let json = JsonModel::load(json_path).wrap_err("Invalid parameter for <JSON>")?;
+        let json_paths = match matches.get_many::<std::path::PathBuf>("JSON") {
+            Some(paths) => paths.cloned().collect::<Vec<_>>(),
+            None => vec![Builder::discover_config()?],
+        };
+
+        let mut jsons = vec![];
+        for json_path in json_paths.iter() {
+            let json_path =
+                utils::path_or_err(json_path).wrap_err("Invalid parameter for <JSON>")?;
+            match WorkspaceModel::load(&json_path).wrap_err("Invalid parameter for <JSON>")? {
+                None => jsons
+                    .push(JsonModel::load(&json_path).wrap_err("Invalid parameter for <JSON>")?),
+                Some(members) => jsons.extend(members),
+            }
+        }
 
-        let tidy_file = match self.matches.contains_id("tidy") {
+        let roots: Vec<_> = json_paths
+            .iter()
+            .map(|path| path.canonicalize().unwrap().parent().unwrap().to_path_buf())
+            .collect();
+        let root = utils::common_ancestor(&roots).unwrap_or_else(|| roots[0].clone());
+        let name = json_paths
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let tidy_file = match matches.contains_id("tidy") {
             false => None,
             true => {
-                let tidy_path = self
-                    .path_for_key("tidy", true)
+                let tidy_path = Builder::path_for_key(matches, "tidy", true)
                     .wrap_err("Invalid parameter for option --tidy")?;
                 let path = utils::file_with_name_or_ext(tidy_path, ".clang-tidy")
                     .wrap_err("Invalid parameter for option --tidy")?;
@@ -179,23 +859,26 @@ impl Builder {
             }
         };
 
-        let command = match self.matches.get_one::<std::path::PathBuf>("command") {
+        let command = match matches.get_one::<std::path::PathBuf>("command") {
             None => None,
             Some(_) => Some(
-                utils::executable_or_exists(self.path_for_key("command", false)?, None)
-                    .wrap_err("Invalid parameter for option --command")
-                    .suggestion(
-                        "Please make sure that '--command' is either a valid absolute path, \
+                utils::executable_or_exists(
+                    Builder::path_for_key(matches, "command", false)?,
+                    None,
+                )
+                .wrap_err("Invalid parameter for option --command")
+                .suggestion(
+                    "Please make sure that '--command' is either a valid absolute path, \
                             a valid path relative to the current working directory \
                             or a known application",
-                    )?,
+                )?,
             ),
         };
 
-        let build_root = match self.matches.get_one::<std::path::PathBuf>("build-root") {
+        let build_root = match matches.get_one::<std::path::PathBuf>("build-root") {
             None => None,
             Some(_) => Some(
-                utils::dir_or_err(self.path_for_key("build-root", false)?)
+                utils::dir_or_err(Builder::path_for_key(matches, "build-root", false)?)
                     .wrap_err("Invalid parameter for option --build-root")
                     .suggestion(
                         "Please make sure that '--build-root' is either a valid absolute path or \
@@ -204,8 +887,32 @@ impl Builder {
             ),
         };
 
+        let tidy_root = match matches.get_one::<std::path::PathBuf>("tidy-root") {
+            None => None,
+            Some(_) => Some(
+                utils::dir_or_err(Builder::path_for_key(matches, "tidy-root", false)?)
+                    .wrap_err("Invalid parameter for option --tidy-root")
+                    .suggestion(
+                        "Please make sure that '--tidy-root' is either a valid absolute path or \
+                            a valid path relative to the current working directory",
+                    )?,
+            ),
+        };
+
+        let tidy_config = match matches.get_one::<String>("tidy-config") {
+            None => None,
+            Some(config) => Some(
+                serde_json::from_str::<TidyConfig>(config)
+                    .wrap_err("Invalid parameter for option --tidy-config")
+                    .suggestion(
+                        "Please provide a valid JSON object matching the field 'tidyConfig' of \
+                         the configuration file",
+                    )?,
+            ),
+        };
+
         let jobs = {
-            if let Some(val) = self.matches.get_one::<String>("jobs") {
+            if let Some(val) = matches.get_one::<String>("jobs") {
                 let val: u8 = val
                     .parse()
                     .map_err(|_| eyre!("Invalid parameter for option --jobs"))
@@ -216,22 +923,112 @@ impl Builder {
             }
         };
 
+        let report = match matches.get_one::<String>("report-format") {
+            None => None,
+            Some(format) => Some(Report {
+                format: match format.as_str() {
+                    "sarif" => ReportFormat::Sarif,
+                    "junit" => ReportFormat::Junit,
+                    "json" => ReportFormat::Json,
+                    _ => unreachable!(),
+                },
+                file: Builder::path_for_key(matches, "report-file", false)?,
+                warnings_as_failures: matches
+                    .get_one::<String>("junit-warnings")
+                    .is_some_and(|outcome| outcome == "failure"),
+            }),
+        };
+
+        let baseline = match matches.get_one::<std::path::PathBuf>("baseline") {
+            None => None,
+            Some(file) => Some(Baseline {
+                file: file.clone(),
+                update: self.matches.subcommand_matches("baseline").is_some(),
+            }),
+        };
+
+        // empty values are dropped such that, e.g., '--filter-pre=' overrides with an empty list
+        let list = |key: &str| {
+            matches.get_many::<String>(key).map(|values| {
+                values
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+        };
+        let string = |key: &str| matches.get_one::<String>(key).cloned();
+
         Ok(Data {
-            json,
+            jsons,
+            root,
+            name,
             tidy_file,
+            tidy_root,
+            tidy_config,
+            paths: list("paths"),
+            source: match matches.get_one::<String>("source").map(String::as_str) {
+                None => None,
+                Some("compileCommands") => Some(Source::CompileCommands),
+                Some("both") => Some(Source::Both),
+                Some(_) => Some(Source::Paths),
+            },
+            filter_pre: list("filter-pre"),
+            filter_post: list("filter-post"),
+            extra_args: list("extra-arg"),
+            extra_args_before: list("extra-arg-before"),
+            checks: string("checks"),
+            header_filter: string("header-filter"),
+            warnings_as_errors: string("warnings-as-errors"),
+            timeout: matches.get_one::<u64>("timeout").copied(),
             build_root,
             command,
             jobs,
-            ignore_warn: self.matches.get_flag("suppress-warnings"),
+            ignore_warn: matches.get_flag("suppress-warnings"),
             // TODO: replace quiet flag with own logger implementation.
-            quiet: self.matches.get_flag("quiet"),
-            fix: self.matches.get_flag("fix"),
+            quiet: matches.get_flag("quiet"),
+            fix: matches.get_flag("fix"),
+            export_fixes: matches
+                .get_one::<std::path::PathBuf>("export-fixes")
+                .cloned(),
+            dry_run: matches.get_flag("dry-run"),
+            patch_file: matches.get_one::<std::path::PathBuf>("patch-file").cloned(),
+            report,
+            output: match matches.get_one::<String>("output").map(String::as_str) {
+                Some("json") => OutputFormat::Json,
+                _ => OutputFormat::Human,
+            },
+            baseline,
+            changed_since: matches.get_one::<String>("changed-since").cloned(),
+            cache_dir: matches.get_one::<std::path::PathBuf>("cache-dir").cloned(),
+            missing_commands: match matches
+                .get_one::<String>("missing-commands")
+                .map(String::as_str)
+            {
+                Some("skip") => Some(MissingCommands::Skip),
+                Some("warn") => Some(MissingCommands::Warn),
+                Some("fail") => Some(MissingCommands::Fail),
+                _ => None,
+            },
+            max_failures: match matches.get_flag("fail-fast") {
+                true => Some(1),
+                false => matches
+                    .get_one::<u64>("max-failures")
+                    .map(|max| *max as usize),
+            },
+            tidy_args: matches
+                .get_many::<String>("tidy-args")
+                .map(|args| args.cloned().collect())
+                .unwrap_or_default(),
+            check: self.matches.subcommand_matches("check-config").is_some(),
         })
     }
 
-    fn path_for_key(&self, key: &str, check_exists: bool) -> eyre::Result<path::PathBuf> {
-        let path = self
-            .matches
+    fn path_for_key(
+        matches: &clap::ArgMatches,
+        key: &str,
+        check_exists: bool,
+    ) -> eyre::Result<path::PathBuf> {
+        let path = matches
             .get_one::<std::path::PathBuf>(key)
             .map(std::path::PathBuf::from)
             .ok_or(eyre!(format!(
@@ -245,23 +1042,140 @@ impl Builder {
     }
 }
 
+impl WorkspaceModel {
+    /// Loads the configuration files of all members if `path` is a workspace file, i.e., a
+    /// configuration file with the field "members". Returns `None` for any other file.
+    fn load(path: &path::Path) -> eyre::Result<Option<Vec<JsonModel>>> {
+        let name = path.to_string_lossy();
+        let mut value = match Format::read(path) {
+            Ok(value) => value,
+            // invalid files are reported when loading the configuration
+            Err(_) => return Ok(None),
+        };
+        if value.get("members").is_none() {
+            return Ok(None);
+        }
+        utils::expand_env(&mut value)
+            .wrap_err(format!(
+                "Failed to expand environment variables in '{name}'"
+            ))
+            .suggestion("Please define the environment variable or remove the reference")?;
+
+        let workspace: WorkspaceModel = serde_json::from_value(value)
+            .wrap_err(format!("Validation failed for workspace '{name}'"))
+            .suggestion("A workspace file must only contain the field 'members'")?;
+        if workspace.members.is_empty() {
+            return Err(eyre!("The workspace '{name}' has no members"))
+                .suggestion("Please add the paths of the configuration files to 'members'");
+        }
+
+        let root = path.canonicalize().unwrap().parent().unwrap().to_path_buf();
+        let mut members = vec![];
+        for member in workspace.members.iter() {
+            let member = root.join(member);
+            let wrap = || {
+                format!(
+                    "Invalid member '{}' of workspace '{name}'",
+                    member.display()
+                )
+            };
+
+            let member = utils::path_or_err(member.as_path()).wrap_err_with(wrap)?;
+            if WorkspaceModel::load(&member).wrap_err_with(wrap)?.is_some() {
+                return Err(eyre!("Nested workspaces are not supported")).wrap_err_with(wrap);
+            }
+            members.push(JsonModel::load(&member).wrap_err_with(wrap)?);
+        }
+        Ok(Some(members))
+    }
+}
+
 impl JsonModel {
+    /// Provides all paths and globs of the field "paths", including the ones of all groups.
+    pub fn globs(&self) -> Vec<String> {
+        self.paths
+            .iter()
+            .flatten()
+            .flat_map(|entry| match entry {
+                PathEntry::Glob(glob) => vec![glob.clone()],
+                PathEntry::Group(group) => group.paths.clone(),
+            })
+            .collect()
+    }
+
+    /// Provides all groups of the field "paths".
+    pub fn groups(&self) -> Vec<&PathGroup> {
+        self.paths
+            .iter()
+            .flatten()
+            .filter_map(|entry| match entry {
+                PathEntry::Glob(_) => None,
+                PathEntry::Group(group) => Some(group),
+            })
+            .collect()
+    }
+
     fn schema() -> String {
         let schema = schema_for!(JsonModel);
         serde_json::to_string_pretty(&schema).unwrap()
     }
 
     fn load(path: impl AsRef<path::Path>) -> eyre::Result<JsonModel> {
-        let json_path = utils::file_with_ext(path.as_ref(), "json", true)?;
+        JsonModel::load_extended(path.as_ref(), &mut vec![])
+    }
+
+    /// Loads the configuration file and merges it with its base configurations, if any. The
+    /// `chain` contains all files that are extended by the currently loaded file.
+    fn load_extended(path: &path::Path, chain: &mut Vec<path::PathBuf>) -> eyre::Result<JsonModel> {
+        let json = JsonModel::read(path)?;
+        let file = path.canonicalize().unwrap();
+        if chain.contains(&file) {
+            return Err(eyre!("Cyclic 'extends' detected for '{}'", json.name))
+                .suggestion("Please make sure that a configuration does not extend itself");
+        }
+
+        let base = match &json.extends {
+            None => return Ok(json),
+            Some(base) => json.root.join(base),
+        };
+        chain.push(file);
+
+        let base = JsonModel::load_extended(&base, chain)
+            .wrap_err(format!(
+                "Failed to load the base configuration '{}' of '{}'",
+                base.to_string_lossy(),
+                json.name
+            ))
+            .suggestion(format!(
+                "Check the content of the field 'extends' in {}.",
+                json.name
+            ))?;
+        json.merge(base)
+    }
+
+    fn read(path: &path::Path) -> eyre::Result<JsonModel> {
+        let json_path = utils::file_or_err(path)?;
         let json_name = json_path.to_string_lossy();
 
-        let f = std::fs::File::open(path.as_ref())
-            .wrap_err(format!("Failed to open provided JSON file '{json_name}'"))?;
+        let mut value = Format::read(path)?;
+        utils::expand_env(&mut value)
+            .wrap_err(format!(
+                "Failed to expand environment variables in '{json_name}'"
+            ))
+            .suggestion("Please define the environment variable or remove the reference")?;
 
-        let mut json: JsonModel = serde_json::from_reader(std::io::BufReader::new(f))
+        let mut json: JsonModel = serde_json::from_value(value)
             .wrap_err(format!("Validation failed for '{json_name}'"))
             .suggestion(format!(
-        "Please make sure that '{json_name}' is a valid .json file and the contents match the required schema."))?;
+                "Please make sure that the contents of '{json_name}' match the required schema."
+            ))?;
+
+        if json.tidy_file.is_some() && json.tidy_config.is_some() {
+            return Err(eyre!(
+                "The fields 'tidyFile' and 'tidyConfig' in '{json_name}' are mutually exclusive"
+            ))
+            .suggestion("Please remove one of the fields 'tidyFile' or 'tidyConfig'");
+        }
 
         json.root = json_path
             .canonicalize()
@@ -273,4 +1187,173 @@ impl JsonModel {
         json.name = json_path.to_string_lossy().into();
         Ok(json)
     }
+
+    /// Merges this configuration with its `base` configuration. Relative paths of the base
+    /// configuration are resolved against its own root, such that they remain valid within the
+    /// merged configuration.
+    fn merge(self, base: JsonModel) -> eyre::Result<JsonModel> {
+        // globs are resolved relative to the root of the configuration and cannot be absolute
+        let prefix = utils::glob_prefix(&base.root, &self.root).ok_or(eyre!(
+            "The base configuration '{}' of '{}' does not share a common root",
+            base.name,
+            self.name
+        ))?;
+        let rebase_glob = |glob: String| utils::prefix_glob(&prefix, glob);
+        let rebase_path = |path: path::PathBuf| base.root.join(path);
+
+        let base_paths = base.paths.map(|paths| {
+            paths
+                .into_iter()
+                .map(|entry| match entry {
+                    PathEntry::Glob(glob) => PathEntry::Glob(rebase_glob(glob)),
+                    PathEntry::Group(group) => PathEntry::Group(PathGroup {
+                        paths: group.paths.into_iter().map(rebase_glob).collect(),
+                        tidy_file: group.tidy_file.map(rebase_path),
+                        ..group
+                    }),
+                })
+                .collect()
+        });
+
+        fn append<T>(
+            replace: bool,
+            base: Option<Vec<T>>,
+            derived: Option<Vec<T>>,
+        ) -> Option<Vec<T>> {
+            match (base, derived) {
+                (_, derived) if replace => derived,
+                (None, derived) => derived,
+                (base, None) => base,
+                (Some(mut base), Some(derived)) => {
+                    base.extend(derived);
+                    Some(base)
+                }
+            }
+        }
+        let replace = self.replace.unwrap_or_default();
+        let replaces = |field| replace.contains(&field);
+
+        // the tidy file and the inline configuration are mutually exclusive, specifying any of
+        // them replaces both fields of the base configuration
+        let (tidy_file, tidy_config) = match (&self.tidy_file, &self.tidy_config) {
+            (None, None) => (base.tidy_file.map(rebase_path), base.tidy_config),
+            _ => (self.tidy_file, self.tidy_config),
+        };
+
+        Ok(JsonModel {
+            extends: self.extends,
+            replace: None,
+            paths: append(replaces(ListField::Paths), base_paths, self.paths),
+            source: self.source.or(base.source),
+            filter_pre: append(
+                replaces(ListField::FilterPre),
+                base.filter_pre,
+                self.filter_pre,
+            ),
+            filter_post: append(
+                replaces(ListField::FilterPost),
+                base.filter_post,
+                self.filter_post,
+            ),
+            tidy_file,
+            tidy_config,
+            tidy_root: self.tidy_root.or(base.tidy_root.map(rebase_path)),
+            build_root: self.build_root.or(base.build_root.map(rebase_path)),
+            // command names are searched in the path and must therefore not be resolved
+            command: self.command.or(base.command.map(|command| {
+                match command.components().count() {
+                    1 => command,
+                    _ => rebase_path(command),
+                }
+            })),
+            extra_args: append(
+                replaces(ListField::ExtraArgs),
+                base.extra_args,
+                self.extra_args,
+            ),
+            extra_args_before: append(
+                replaces(ListField::ExtraArgsBefore),
+                base.extra_args_before,
+                self.extra_args_before,
+            ),
+            checks: self.checks.or(base.checks),
+            header_filter: self.header_filter.or(base.header_filter),
+            warnings_as_errors: self.warnings_as_errors.or(base.warnings_as_errors),
+            timeout: self.timeout.or(base.timeout),
+            root: self.root,
+            name: self.name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_merge() {
+        let load = |json: &str, root: &str| {
+            let mut json: JsonModel = serde_json::from_str(json).unwrap();
+            json.root = path::PathBuf::from(root);
+            json
+        };
+
+        let base = load(
+            r#"{
+                "paths": ["src/**/*.c"],
+                "filterPre": [".git"],
+                "tidyFile": ".clang-tidy",
+                "command": "clang-tidy-14",
+                "buildRoot": "_bld",
+                "checks": "-*,bugprone-*"
+            }"#,
+            "/prj",
+        );
+        let json = load(
+            r#"{
+                "paths": ["lib/**/*.c"],
+                "filterPre": ["third_party"],
+                "tidyConfig": { "checks": ["-*"] },
+                "checks": "-*,readability-*"
+            }"#,
+            "/prj/cfg",
+        )
+        .merge(base)
+        .unwrap();
+
+        assert_eq!(vec!["../src/**/*.c", "lib/**/*.c"], json.globs());
+        assert_eq!(
+            Some(vec![".git".to_string(), "third_party".to_string()]),
+            json.filter_pre
+        );
+        // the inline configuration replaces the tidy file of the base configuration
+        assert!(json.tidy_file.is_none() && json.tidy_config.is_some());
+        assert_eq!(Some(path::PathBuf::from("clang-tidy-14")), json.command);
+        assert_eq!(Some(path::PathBuf::from("/prj/_bld")), json.build_root);
+        assert_eq!(Some("-*,readability-*".to_string()), json.checks);
+        assert_eq!(path::PathBuf::from("/prj/cfg"), json.root);
+
+        // listed lists replace the lists of the base configuration
+        let base = load(
+            r#"{ "paths": ["src/**/*.c"], "filterPre": [".git"], "extraArgs": ["-DA"] }"#,
+            "/prj",
+        );
+        let json = load(
+            r#"{
+                "replace": ["paths", "filterPre"],
+                "paths": ["lib/**/*.c"],
+                "extraArgs": ["-DB"]
+            }"#,
+            "/prj",
+        )
+        .merge(base)
+        .unwrap();
+        assert_eq!(vec!["lib/**/*.c"], json.globs());
+        assert_eq!(None, json.filter_pre);
+        assert_eq!(
+            Some(vec!["-DA".to_string(), "-DB".to_string()]),
+            json.extra_args
+        );
+    }
 }
@@ -211,6 +211,120 @@ where
     filename_or_exists_with_ext(path, root, ext)
 }
 
+/// Provides `path` relative to `base`, e.g., `../src/main.c` for the path `/prj/src/main.c` and the
+/// base directory `/prj/cfg`. Both paths are expected to be absolute; `None` is returned if they do
+/// not share a common root (e.g., different drives on Windows).
+pub fn relative_to<P, Q>(path: P, base: Q) -> Option<path::PathBuf>
+where
+    P: AsRef<path::Path>,
+    Q: AsRef<path::Path>,
+{
+    let mut path_iter = path.as_ref().components().peekable();
+    let mut base_iter = base.as_ref().components().peekable();
+
+    match (path_iter.peek(), base_iter.peek()) {
+        (Some(p), Some(b)) if p == b => (),
+        _ => return None,
+    }
+
+    while let (Some(p), Some(b)) = (path_iter.peek(), base_iter.peek()) {
+        if p != b {
+            break;
+        }
+        path_iter.next();
+        base_iter.next();
+    }
+
+    let mut relative = path::PathBuf::new();
+    base_iter.for_each(|_| relative.push(".."));
+    path_iter.for_each(|c| relative.push(c));
+    Some(relative)
+}
+
+/// Provides the prefix for globs specified relative to `from` that are resolved relative to the
+/// directory `to`, e.g., `../cfg` for `/prj/cfg` and `/prj/src`. Globs always use forward slashes.
+pub fn glob_prefix<P, Q>(from: P, to: Q) -> Option<String>
+where
+    P: AsRef<path::Path>,
+    Q: AsRef<path::Path>,
+{
+    let relative = relative_to(from, to)?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Prepends the `prefix` provided by `glob_prefix` to the `glob`.
+pub fn prefix_glob(prefix: &str, glob: String) -> String {
+    match prefix.is_empty() {
+        true => glob,
+        false => format!("{prefix}/{glob}"),
+    }
+}
+
+/// Expands all references `${VAR}` to environment variables within the string values of `value`.
+pub fn expand_env(value: &mut serde_json::Value) -> eyre::Result<()> {
+    match value {
+        serde_json::Value::String(string) => {
+            *string = expand_str(string, |var| std::env::var(var).ok())?
+        }
+        serde_json::Value::Array(values) => {
+            for value in values.iter_mut() {
+                expand_env(value)?;
+            }
+        }
+        serde_json::Value::Object(values) => {
+            for value in values.values_mut() {
+                expand_env(value)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn expand_str(string: &str, lookup: impl Fn(&str) -> Option<String>) -> eyre::Result<String> {
+    let re = regex::Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+
+    let mut expanded = String::with_capacity(string.len());
+    let mut last = 0;
+    for caps in re.captures_iter(string) {
+        let (all, var) = (caps.get(0).unwrap(), &caps[1]);
+        let value = lookup(var).ok_or(eyre!(
+            "Environment variable '{var}' referenced in '{string}' is not defined"
+        ))?;
+        expanded.push_str(&string[last..all.start()]);
+        expanded.push_str(&value);
+        last = all.end();
+    }
+    expanded.push_str(&string[last..]);
+    Ok(expanded)
+}
+
+/// Provides the longest common ancestor of all absolute `paths`, e.g., `/prj` for the paths
+/// `/prj/a/cfg` and `/prj/b`. `None` is returned if there are no paths or they do not share a
+/// common root.
+pub fn common_ancestor<P>(paths: &[P]) -> Option<path::PathBuf>
+where
+    P: AsRef<path::Path>,
+{
+    let (first, others) = paths.split_first()?;
+    first
+        .as_ref()
+        .ancestors()
+        .find(|ancestor| {
+            others
+                .iter()
+                .all(|path| path.as_ref().starts_with(ancestor))
+        })
+        .filter(|ancestor| ancestor.components().next().is_some())
+        .map(path::Path::to_path_buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +336,55 @@ mod tests {
 
         assert_eq!(".clang-format", file_name.to_lowercase());
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_relative_to() {
+        let rel = |path: &str, base: &str| relative_to(path, base).unwrap();
+
+        assert_eq!(
+            path::PathBuf::from("src/main.c"),
+            rel("/prj/src/main.c", "/prj")
+        );
+        assert_eq!(
+            path::PathBuf::from("../src/main.c"),
+            rel("/prj/src/main.c", "/prj/cfg")
+        );
+        assert_eq!(
+            path::PathBuf::from("../../main.c"),
+            rel("/main.c", "/prj/cfg")
+        );
+        assert_eq!(path::PathBuf::from(""), rel("/prj", "/prj"));
+        assert_eq!(None, relative_to("src/main.c", "/prj"));
+    }
+
+    #[test]
+    fn test_expand_str() {
+        let lookup = |var: &str| (var == "SDK").then(|| "/opt/sdk".to_string());
+
+        assert_eq!(
+            "/opt/sdk/include/**",
+            expand_str("${SDK}/include/**", lookup).unwrap()
+        );
+        assert_eq!("$SDK {SDK}", expand_str("$SDK {SDK}", lookup).unwrap());
+        assert!(expand_str("${MISSING}/bin", lookup).is_err());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_common_ancestor() {
+        assert_eq!(
+            Some(path::PathBuf::from("/prj")),
+            common_ancestor(&["/prj/a/cfg", "/prj/b", "/prj/a"])
+        );
+        assert_eq!(
+            Some(path::PathBuf::from("/prj/a")),
+            common_ancestor(&["/prj/a"])
+        );
+        assert_eq!(
+            Some(path::PathBuf::from("/")),
+            common_ancestor(&["/a", "/b"])
+        );
+        assert_eq!(None, common_ancestor::<&str>(&[]));
+    }
 }
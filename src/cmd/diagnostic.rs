@@ -0,0 +1,281 @@
+use std::{fmt, path, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+/// Severity of a diagnostic as reported by `clang-tidy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Note,
+    Remark,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn from_label(label: &str) -> Option<Severity> {
+        match label {
+            "note" => Some(Severity::Note),
+            "remark" => Some(Severity::Remark),
+            "warning" => Some(Severity::Warning),
+            "error" | "fatal error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            Severity::Note => "note",
+            Severity::Remark => "remark",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{str}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Location {
+    pub file: path::PathBuf,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A single finding reported by `clang-tidy`.
+///
+/// Notes that follow a warning or error in the output of `clang-tidy` are attached to the
+/// diagnostic in `notes` (and have the severity `Note`). Fix-it hints are the replacement texts
+/// that `clang-tidy` prints below the caret line of the code snippet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub location: Option<Location>,
+    pub severity: Severity,
+    pub message: String,
+    /// Name of the check, e.g., `bugprone-macro-parentheses` or `clang-diagnostic-error`.
+    pub check: Option<String>,
+    /// Set if the check has been listed in `WarningsAsErrors`.
+    pub warning_as_error: bool,
+    pub notes: Vec<Diagnostic>,
+    pub fixits: Vec<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(loc) = &self.location {
+            write!(
+                f,
+                "{}:{}:{}: ",
+                loc.file.to_string_lossy(),
+                loc.line,
+                loc.column
+            )?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let Some(check) = &self.check {
+            write!(f, " [{check}]")?;
+        }
+        for note in self.notes.iter() {
+            write!(f, "\n{note}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_check(checks: Option<&str>) -> (Option<String>, bool) {
+    // the bracketed suffix may contain multiple comma separated entries, e.g.,
+    // [bugprone-macro-parentheses,-warnings-as-errors]
+    let checks = match checks {
+        None => return (None, false),
+        Some(checks) => checks,
+    };
+
+    let warning_as_error = checks.split(',').any(|c| c == "-warnings-as-errors");
+    let check = checks
+        .split(',')
+        .find(|c| !c.is_empty() && !c.starts_with('-'))
+        .map(String::from);
+    (check, warning_as_error)
+}
+
+/// Parses the output of `clang-tidy` into a list of diagnostics.
+///
+/// Lines that do not belong to a diagnostic, e.g., "N warnings generated." or the hints for
+/// suppressed warnings, are skipped.
+pub fn parse(output: &str) -> Vec<Diagnostic> {
+    // the output of every analyzed file is parsed, the expressions are therefore compiled once
+    static RE_LOC: OnceLock<regex::Regex> = OnceLock::new();
+    static RE_PLAIN: OnceLock<regex::Regex> = OnceLock::new();
+    let re_loc = RE_LOC.get_or_init(|| {
+        regex::Regex::new(
+            r"^(.+?):(\d+):(\d+): (note|remark|warning|error|fatal error): (.*?)(?: \[([^\[\]\s]+)\])?$",
+        )
+        .unwrap()
+    });
+    let re_plain = RE_PLAIN.get_or_init(|| {
+        regex::Regex::new(r"^(warning|error|fatal error): (.*?)(?: \[([^\[\]\s]+)\])?$").unwrap()
+    });
+
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    // the code snippet following a diagnostic: source line, caret line, fix-it hints
+    let mut snippet: Option<Vec<&str>> = None;
+
+    fn attach_snippet(diagnostics: &mut [Diagnostic], snippet: Option<Vec<&str>>) {
+        let snippet = match snippet {
+            Some(snippet) if snippet.len() > 2 => snippet,
+            _ => return,
+        };
+        let fixits = snippet[2..]
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(String::from);
+
+        if let Some(last) = diagnostics.last_mut() {
+            match last.notes.last_mut() {
+                Some(note) => note.fixits.extend(fixits),
+                None => last.fixits.extend(fixits),
+            }
+        }
+    }
+
+    for line in output.lines() {
+        let line = line.trim_end_matches('\r');
+
+        let diagnostic = if let Some(caps) = re_loc.captures(line) {
+            let (check, warning_as_error) = parse_check(caps.get(6).map(|m| m.as_str()));
+            let line_nr = caps[2].parse();
+            let column = caps[3].parse();
+            match (line_nr, column) {
+                (Ok(line_nr), Ok(column)) => Some(Diagnostic {
+                    location: Some(Location {
+                        file: path::PathBuf::from(&caps[1]),
+                        line: line_nr,
+                        column,
+                    }),
+                    severity: Severity::from_label(&caps[4]).unwrap(),
+                    message: caps[5].to_string(),
+                    check,
+                    warning_as_error,
+                    notes: vec![],
+                    fixits: vec![],
+                }),
+                _ => None,
+            }
+        } else if let Some(caps) = re_plain.captures(line) {
+            let (check, warning_as_error) = parse_check(caps.get(3).map(|m| m.as_str()));
+            Some(Diagnostic {
+                location: None,
+                severity: Severity::from_label(&caps[1]).unwrap(),
+                message: caps[2].to_string(),
+                check,
+                warning_as_error,
+                notes: vec![],
+                fixits: vec![],
+            })
+        } else {
+            None
+        };
+
+        match diagnostic {
+            Some(diagnostic) => {
+                attach_snippet(&mut diagnostics, snippet.take());
+                snippet = Some(vec![]);
+
+                match (diagnostic.severity, diagnostics.last_mut()) {
+                    (Severity::Note, Some(last)) => last.notes.push(diagnostic),
+                    // orphaned notes are kept as standalone diagnostics
+                    _ => diagnostics.push(diagnostic),
+                }
+            }
+            None => {
+                if let Some(lines) = snippet.as_mut() {
+                    // a snippet only consists of the source line, the caret line and an optional
+                    // fix-it hint that is aligned with the caret; anything else ends the snippet
+                    let accept = match lines.len() {
+                        0 => true,
+                        1 => line.contains('^'),
+                        2 => line.starts_with(char::is_whitespace) && !line.trim().is_empty(),
+                        _ => false,
+                    };
+                    if accept {
+                        lines.push(line);
+                    } else {
+                        attach_snippet(&mut diagnostics, snippet.take());
+                    }
+                }
+            }
+        }
+    }
+    attach_snippet(&mut diagnostics, snippet.take());
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "\
+/some/path/module_fix.h:4:39: error: macro argument should be enclosed in parentheses [bugprone-macro-parentheses,-warnings-as-errors]
+#define MODULE_FIX_EXPRESSION(a, b) a + b
+                                    ^
+                                    ( )
+/some/path/main.c:8:35: warning: integer literal has suffix 'uL', which is not uppercase [readability-uppercase-literal-suffix]
+    unsigned int some_variable = MODULE_UNTIDY_SMTH;
+                                  ^
+/some/path/module_untidy.h:7:30: note: expanded from macro 'MODULE_UNTIDY_SMTH'
+#define MODULE_UNTIDY_SMTH 2uL
+                             ^~~
+                              UL
+Suppressed 2 warnings (2 in non-user code).
+Use -header-filter=.* to display errors from all non-system headers.
+error: unable to handle compilation, expected exactly one compiler job [clang-diagnostic-error]
+";
+
+    #[test]
+    fn test_parse() {
+        let diagnostics = parse(OUTPUT);
+        assert_eq!(3, diagnostics.len());
+
+        let first = &diagnostics[0];
+        assert_eq!(Severity::Error, first.severity);
+        assert_eq!(Some("bugprone-macro-parentheses".to_string()), first.check);
+        assert!(first.warning_as_error);
+        assert_eq!(
+            Some(Location {
+                file: "/some/path/module_fix.h".into(),
+                line: 4,
+                column: 39
+            }),
+            first.location
+        );
+        assert_eq!(vec!["( )".to_string()], first.fixits);
+        assert!(first.notes.is_empty());
+
+        let second = &diagnostics[1];
+        assert_eq!(Severity::Warning, second.severity);
+        assert!(!second.warning_as_error);
+        assert!(second.fixits.is_empty());
+        assert_eq!(1, second.notes.len());
+        assert_eq!(Severity::Note, second.notes[0].severity);
+        assert_eq!(vec!["UL".to_string()], second.notes[0].fixits);
+
+        let third = &diagnostics[2];
+        assert_eq!(None, third.location);
+        assert_eq!(Some("clang-diagnostic-error".to_string()), third.check);
+    }
+
+    #[test]
+    fn test_parse_windows_path() {
+        let diagnostics =
+            parse("C:\\some\\path\\main.c:12:5: warning: some message [some-check]\r\n");
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            path::PathBuf::from("C:\\some\\path\\main.c"),
+            diagnostics[0].location.as_ref().unwrap().file
+        );
+        assert_eq!(12, diagnostics[0].location.as_ref().unwrap().line);
+    }
+}
@@ -1,4 +1,12 @@
-use std::{io, path, process, str::FromStr};
+use std::{
+    io, path, process,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread, time,
+};
+
+mod diagnostic;
+pub use diagnostic::{Diagnostic, Location, Severity};
 
 #[derive(Clone)]
 struct Version {
@@ -7,16 +15,68 @@ struct Version {
     patch: u8,
 }
 
+/// Output of a single `clang-tidy` execution.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// Human readable message, containing the raw output of `clang-tidy`.
+    pub msg: String,
+    /// Diagnostics parsed from the output of `clang-tidy`.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Output {
+    fn new(msg: String, raw: &str) -> Output {
+        Output {
+            msg,
+            diagnostics: diagnostic::parse(raw),
+        }
+    }
+
+    /// Number of diagnostics with the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+
+    /// Short summary of the diagnostics, e.g., "1 error, 2 warnings".
+    pub fn summary(&self) -> String {
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+
+        let mut counts = vec![];
+        if errors > 0 {
+            counts.push(format!(
+                "{errors} error{}",
+                if errors == 1 { "" } else { "s" }
+            ));
+        }
+        if warnings > 0 {
+            counts.push(format!(
+                "{warnings} warning{}",
+                if warnings == 1 { "" } else { "s" }
+            ));
+        }
+        counts.join(", ")
+    }
+}
+
 #[derive(Debug)]
 pub enum RunResult {
     Ok,
-    Err(String),
-    Warn(String),
+    Err(Output),
+    Warn(Output),
+    /// `clang-tidy` did not finish within the configured timeout and has been terminated.
+    Timeout(Output),
 }
 
 impl From<&io::Error> for RunResult {
     fn from(value: &io::Error) -> Self {
-        RunResult::Err(value.to_string())
+        RunResult::Err(Output {
+            msg: value.to_string(),
+            diagnostics: vec![],
+        })
     }
 }
 
@@ -38,6 +98,7 @@ impl FromStr for Version {
 pub struct Runner {
     cmd: path::PathBuf,
     version: Option<Version>,
+    timeout: Option<time::Duration>,
 }
 
 impl Runner {
@@ -46,7 +107,16 @@ impl Runner {
         P: AsRef<path::Path>,
     {
         let cmd = path::PathBuf::from(path.as_ref());
-        Runner { cmd, version: None }
+        Runner {
+            cmd,
+            version: None,
+            timeout: None,
+        }
+    }
+
+    /// Sets the time after which a `clang-tidy` execution is terminated.
+    pub fn set_timeout(&mut self, timeout: Option<time::Duration>) {
+        self.timeout = timeout;
     }
 
     fn eval_status(status: process::ExitStatus) -> Result<(), io::Error> {
@@ -100,28 +170,136 @@ impl Runner {
         Ok(())
     }
 
-    fn run(mut cmd: process::Command, ignore_warn: bool) -> RunResult {
-        let output = cmd.output();
+    /// Reads the pipe of a child process in a separate thread, such that the child never blocks
+    /// on a full pipe while its exit status is polled. The output is collected in a shared buffer
+    /// since the pipe may remain open after a timeout, e.g., if the child spawned a subprocess.
+    fn read_pipe<R>(pipe: Option<R>) -> (Arc<Mutex<Vec<u8>>>, thread::JoinHandle<()>)
+    where
+        R: io::Read + Send + 'static,
+    {
+        let buf = Arc::new(Mutex::new(vec![]));
+        let shared = Arc::clone(&buf);
+        let handle = thread::spawn(move || {
+            let Some(mut pipe) = pipe else {
+                return;
+            };
+            let mut chunk = [0u8; 4096];
+            while let Ok(n) = pipe.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                shared.lock().unwrap().extend_from_slice(&chunk[..n]);
+            }
+        });
+        (buf, handle)
+    }
+
+    /// Executes the command, terminating it once the `timeout` expired. Returns `None` in case of
+    /// a timeout, together with the output produced until the command was terminated.
+    ///
+    /// Only the command itself is terminated, not the processes it started. Since such processes
+    /// inherit the pipes for the output, this function returns only once they exited as well.
+    fn output(
+        cmd: &mut process::Command,
+        timeout: Option<time::Duration>,
+    ) -> io::Result<(Option<process::ExitStatus>, Vec<u8>, Vec<u8>)> {
+        // the command must never wait for input, e.g., when executed in a terminal
+        cmd.stdin(process::Stdio::null());
+        let Some(timeout) = timeout else {
+            let output = cmd.output()?;
+            return Ok((Some(output.status), output.stdout, output.stderr));
+        };
+
+        let mut child = cmd
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()?;
+        let (stdout, stdout_reader) = Runner::read_pipe(child.stdout.take());
+        let (stderr, stderr_reader) = Runner::read_pipe(child.stderr.take());
+
+        let start = time::Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if start.elapsed() >= timeout {
+                // the process might have exited in the meantime, thus errors are ignored
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            thread::sleep(time::Duration::from_millis(10));
+        };
+
+        // all output has been written once the pipes are closed
+        let _ = stdout_reader.join();
+        let _ = stderr_reader.join();
+
+        let take = |buf: Arc<Mutex<Vec<u8>>>| std::mem::take(&mut *buf.lock().unwrap());
+        Ok((status, take(stdout), take(stderr)))
+    }
+
+    fn run(
+        mut cmd: process::Command,
+        ignore_warn: bool,
+        timeout: Option<time::Duration>,
+    ) -> RunResult {
+        let output = Runner::output(&mut cmd, timeout);
         if let Err(err) = &output {
             return err.into();
         }
-        let output = output.unwrap();
+        let (status, stdout, stderr) = output.unwrap();
+
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stdout = String::from_utf8_lossy(&stdout);
 
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        // diagnostics are printed to stdout, whereas stderr contains, e.g., the number of
+        // generated warnings. both are parsed since compiler errors might end up in either
+        let raw = format!("{stdout}\n{stderr}");
 
-        if let Err(err) = Runner::eval_status(output.status) {
-            if !stderr.is_empty() {
-                return RunResult::Err(format!("{err}\n---\n{stderr}---\n{stdout}"));
+        let Some(status) = status else {
+            return RunResult::Timeout(Output::new(
+                format!(
+                    "Process terminated after exceeding the timeout of {}s\n---\n{stderr}---\n{stdout}",
+                    timeout.unwrap_or_default().as_secs()
+                ),
+                &raw,
+            ));
+        };
+
+        let output = Output::new(String::new(), &raw);
+        // with --quiet, clang-tidy prints the diagnostics to stdout only and stderr is empty
+        let has_findings = output
+            .diagnostics
+            .iter()
+            .any(|d| d.severity >= Severity::Warning);
+
+        if let Err(err) = Runner::eval_status(status) {
+            if stderr.is_empty() && stdout.is_empty() {
+                return (&err).into();
             }
-            return (&err).into();
-        } else if !ignore_warn && !stderr.is_empty() {
-            return RunResult::Warn(format!("warnings encountered\n---\n{stderr}---\n{stdout}"));
+            return RunResult::Err(Output {
+                msg: format!("{err}\n---\n{stderr}---\n{stdout}"),
+                ..output
+            });
+        } else if !ignore_warn && (has_findings || !stderr.is_empty()) {
+            return RunResult::Warn(Output {
+                msg: format!("warnings encountered\n---\n{stderr}---\n{stdout}"),
+                ..output
+            });
         }
         RunResult::Ok
     }
 
-    pub fn run_tidy<P, Q>(&self, file: P, build_root: Q, fix: bool, ignore_warn: bool) -> RunResult
+    pub fn run_tidy<P, Q>(
+        &self,
+        file: P,
+        build_root: Q,
+        config_file: Option<&path::Path>,
+        args: &[String],
+        export_fixes: Option<&path::Path>,
+        ignore_warn: bool,
+    ) -> RunResult
     where
         P: AsRef<path::Path>,
         Q: AsRef<path::Path>,
@@ -129,36 +307,36 @@ impl Runner {
         let mut cmd = process::Command::new(self.cmd.as_path());
 
         cmd.arg(file.as_ref().as_os_str());
-        // TODO: the --config-file option does not exist for clang-tidy 10.0
-        // if let Some(config_file) = config_file {
-        //     cmd.arg(format!(
-        //         "--config-file={}",
-        //         config_file.as_ref().to_string_lossy()
-        //     ));
-        // }
+        // the --config-file option does not exist before clang-tidy 12.0, see supports_config_file
+        if let Some(config_file) = config_file {
+            cmd.arg(format!("--config-file={}", config_file.to_string_lossy()));
+        }
         cmd.arg(format!("-p={}", build_root.as_ref().to_string_lossy()));
-        if fix {
-            cmd.arg("-fix").arg("-fix-errors");
+        // fixes are never applied in place since parallel executions may modify the same
+        // header, they are exported and applied once all files have been analyzed
+        if let Some(file) = export_fixes {
+            cmd.arg(format!("-export-fixes={}", file.to_string_lossy()));
         }
+        cmd.args(args);
         // This suppresses printing statistics about ignored warnings:
         // cmd.arg("-quiet");
 
-        Runner::run(cmd, ignore_warn)
+        Runner::run(cmd, ignore_warn, self.timeout)
     }
 
     pub fn supports_config_file(&self) -> Result<(), io::Error> {
         if self.version.is_none() {
             return Err(io::Error::other(
                 "Unknown version, --config-file requires \
-                clang-format version 12.0.0 or higher",
+                clang-tidy version 12.0.0 or higher",
             ));
         }
 
         let version = self.version.as_ref().unwrap();
-        if version.major < 9u8 {
+        if version.major < 12u8 {
             return Err(io::Error::other(format!(
-                "Invalid version {}, --config-file check requires \
-                    clang-format version 12.0.0 or higher",
+                "Invalid version {}, --config-file requires \
+                    clang-tidy version 12.0.0 or higher",
                 self.get_version().unwrap()
             )));
         }
@@ -172,6 +350,72 @@ impl Clone for Runner {
         Runner {
             cmd: path::PathBuf::from(self.cmd.as_path()),
             version: self.version.clone(),
+            timeout: self.timeout,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    fn sh(script: &str) -> process::Command {
+        let mut cmd = process::Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_run_outcome() {
+        let warning = "echo 'a.c:1:2: warning: msg [bugprone-macro-parentheses]'";
+
+        // diagnostics on stdout are reported even if stderr is empty, e.g., using --quiet
+        match Runner::run(sh(warning), false, None) {
+            RunResult::Warn(output) => assert_eq!(1, output.count(Severity::Warning)),
+            result => panic!("Unexpected result {result:?}"),
+        }
+        assert!(matches!(
+            Runner::run(sh(warning), true, None),
+            RunResult::Ok
+        ));
+        assert!(matches!(
+            Runner::run(sh("echo '1 warning generated.' >&2"), false, None),
+            RunResult::Warn(_)
+        ));
+        assert!(matches!(
+            Runner::run(sh("echo 'no diagnostics'"), false, None),
+            RunResult::Ok
+        ));
+
+        // the output is kept for failures with an empty stderr
+        match Runner::run(sh(&format!("{warning}; exit 1")), true, None) {
+            RunResult::Err(output) => {
+                assert!(output.msg.contains("bugprone-macro-parentheses"));
+                assert_eq!(1, output.diagnostics.len());
+            }
+            result => panic!("Unexpected result {result:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_run_timeout() {
+        let timeout = Some(time::Duration::from_secs(1));
+
+        // the shell is replaced such that no subprocess keeps the pipes open once terminated
+        let start = time::Instant::now();
+        match Runner::run(sh("echo partial; exec sleep 10"), false, timeout) {
+            RunResult::Timeout(output) => assert!(output.msg.contains("partial")),
+            result => panic!("Unexpected result {result:?}"),
+        }
+        assert!(start.elapsed() < time::Duration::from_secs(5));
+
+        // the complete output is evaluated for commands that finish in time
+        assert!(matches!(
+            Runner::run(sh("echo '1 warning generated.' >&2"), false, timeout),
+            RunResult::Warn(_)
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use std::{collections::HashMap, fs, path};
+
+use serde::Deserialize;
+
+/// Raw entry of a `compile_commands.json` file, see
+/// https://clang.llvm.org/docs/JSONCompilationDatabase.html
+#[derive(Deserialize, Debug)]
+struct RawEntry {
+    directory: path::PathBuf,
+    file: path::PathBuf,
+    arguments: Option<Vec<String>>,
+    command: Option<String>,
+}
+
+/// Compile command for a single translation unit.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Working directory of the compilation.
+    pub directory: path::PathBuf,
+    /// Canonical path of the translation unit.
+    pub file: path::PathBuf,
+    /// Arguments of the compile command, including the compiler.
+    pub arguments: Vec<String>,
+}
+
+impl Entry {
+    /// Provides the include directories of the compile command in the order of their lookup for
+    /// quoted includes `#include "file"` (.0) and angle-bracket includes `#include <file>` (.1).
+    pub fn include_dirs(&self) -> (Vec<path::PathBuf>, Vec<path::PathBuf>) {
+        let mut quote = vec![];
+        let mut user = vec![];
+        let mut system = vec![];
+
+        let mut args = self.arguments.iter();
+        while let Some(arg) = args.next() {
+            let (list, dir) = match ["-iquote", "-I", "-isystem", "-idirafter"]
+                .iter()
+                .find(|flag| arg.starts_with(*flag))
+            {
+                None => continue,
+                Some(flag) => {
+                    let list = match *flag {
+                        "-iquote" => &mut quote,
+                        "-I" => &mut user,
+                        _ => &mut system,
+                    };
+                    match &arg[flag.len()..] {
+                        "" => match args.next() {
+                            Some(dir) => (list, dir.as_str()),
+                            None => break,
+                        },
+                        dir => (list, dir),
+                    }
+                }
+            };
+            list.push(self.directory.join(dir));
+        }
+
+        quote.extend(user.iter().cloned());
+        quote.extend(system.iter().cloned());
+        user.extend(system);
+        (quote, user)
+    }
+}
+
+/// Compilation database, i.e., the contents of a `compile_commands.json` file.
+pub struct Database {
+    entries: HashMap<path::PathBuf, Entry>,
+}
+
+impl Database {
+    /// Loads the `compile_commands.json` from the `build_root`.
+    ///
+    /// The error is provided as plain message, such that the result can be shared by all
+    /// functions that rely on the compilation database.
+    pub fn load(build_root: &path::Path) -> Result<Database, String> {
+        let file = build_root.join("compile_commands.json");
+        let name = file.to_string_lossy();
+
+        let content = fs::read_to_string(&file)
+            .map_err(|err| format!("Failed to read compilation database '{name}': {err}"))?;
+
+        let raw: Vec<RawEntry> = serde_json::from_str(&content)
+            .map_err(|err| format!("Invalid compilation database '{name}': {err}"))?;
+
+        let entries = raw
+            .into_iter()
+            .filter_map(|entry| {
+                let arguments = match (entry.arguments, entry.command) {
+                    (Some(arguments), _) => arguments,
+                    (None, Some(command)) => split_command(&command),
+                    (None, None) => {
+                        log::debug!(
+                            "Skipping entry for '{}' without command in '{name}'",
+                            entry.file.to_string_lossy()
+                        );
+                        return None;
+                    }
+                };
+                let directory = build_root.join(entry.directory);
+                let file = directory.join(entry.file);
+                // entries for files that do not exist (anymore) are of no interest
+                let file = file.canonicalize().ok()?;
+                Some((
+                    file.clone(),
+                    Entry {
+                        directory,
+                        file,
+                        arguments,
+                    },
+                ))
+            })
+            .collect();
+
+        Ok(Database { entries })
+    }
+
+    /// Provides the canonical paths of all translation units.
+    pub fn files(&self) -> Vec<path::PathBuf> {
+        let mut files: Vec<_> = self.entries.keys().cloned().collect();
+        files.sort_unstable();
+        files
+    }
+
+    /// Provides the entry for the canonical `path` of a translation unit.
+    pub fn entry(&self, path: &path::Path) -> Option<&Entry> {
+        self.entries.get(path)
+    }
+}
+
+/// Splits a command line into its arguments, following the quoting rules of a POSIX shell.
+fn split_command(command: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(c) = chars.next() {
+                    arg.get_or_insert_with(String::new).push(c);
+                }
+            }
+            (Some(_), c) => arg.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(arg.take()),
+            (None, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            vec!["gcc", "-DNAME=\"a b\"", "-I", "some dir", "", "main.c"],
+            split_command(r#"gcc  -DNAME=\"a\ b\" -I "some dir" '' main.c "#)
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_include_dirs() {
+        let entry = Entry {
+            directory: "/build".into(),
+            file: "/src/main.c".into(),
+            arguments: split_command("cc -Iinc -isystem /sys -iquote q -I ../other -c main.c"),
+        };
+        let (quote, angle) = entry.include_dirs();
+
+        let expected: Vec<path::PathBuf> = vec![
+            "/build/q".into(),
+            "/build/inc".into(),
+            "/build/../other".into(),
+            "/sys".into(),
+        ];
+        assert_eq!(expected, quote);
+        assert_eq!(expected[1..], angle);
+    }
+}
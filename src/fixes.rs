@@ -0,0 +1,519 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+use serde::{Deserialize, Serialize};
+
+/// Name of the merged replacements file within the export directory.
+const FILE_NAME: &str = "fixes.yaml";
+
+/// Replacements exported by `clang-tidy` using `-export-fixes`, in the format that is also read by
+/// `clang-apply-replacements`. The diagnostics are kept as they are, such that fields that are
+/// not needed for merging are preserved.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Replacements {
+    #[serde(default)]
+    main_source_file: String,
+    #[serde(default)]
+    diagnostics: Vec<serde_norway::Value>,
+}
+
+/// Single edit of a file, the offset and length are in bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Replacement {
+    file_path: path::PathBuf,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacement_text: String,
+}
+
+impl Replacement {
+    fn end(&self) -> usize {
+        self.offset + self.length
+    }
+
+    /// Replacements overlap if they modify the same range of a file. Insertions at the same
+    /// offset overlap as well, since their order is ambiguous.
+    fn overlaps(&self, other: &Replacement) -> bool {
+        self.file_path == other.file_path
+            && ((self.offset < other.end() && other.offset < self.end())
+                || self.offset == other.offset)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Message {
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+}
+
+/// Fix of a diagnostic, i.e., all replacements of its message. Fixes are applied as a whole.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Fix {
+    diagnostic_name: String,
+    diagnostic_message: Message,
+    #[serde(default)]
+    build_directory: path::PathBuf,
+}
+
+impl Fix {
+    /// Provides the replacements with absolute paths, relative paths are resolved against the
+    /// build directory.
+    fn replacements(&self) -> Vec<Replacement> {
+        let mut replacements: Vec<Replacement> = vec![];
+        for replacement in self.diagnostic_message.replacements.iter() {
+            let path = self.build_directory.join(&replacement.file_path);
+            let replacement = Replacement {
+                file_path: path.canonicalize().unwrap_or(path),
+                ..replacement.clone()
+            };
+            if !replacements.contains(&replacement) {
+                replacements.push(replacement);
+            }
+        }
+        replacements
+    }
+}
+
+/// Outcome of applying the fixes.
+pub struct Applied {
+    /// Number of applied fixes.
+    pub fixes: usize,
+    /// Modified files, including headers, in the order of their paths.
+    pub files: Vec<path::PathBuf>,
+    /// Fixes that have not been applied since they conflict with other fixes.
+    pub conflicts: Vec<String>,
+}
+
+/// Fixes exported by `clang-tidy` for all translation units.
+///
+/// `clang-tidy` writes the fixes of each translation unit to a separate file in a temporary
+/// folder. These files are merged once all files have been analyzed, such that fixes in headers
+/// that are included by multiple translation units are only contained once. The merged fixes are
+/// either exported to a single replacements file or applied. The temporary folder is removed when
+/// the export is dropped.
+pub struct Export {
+    dir: Option<path::PathBuf>,
+    tmp: path::PathBuf,
+}
+
+impl Export {
+    /// Creates the temporary folder and, if provided, the directory `dir` for the replacements
+    /// file.
+    pub fn new(dir: Option<&path::Path>) -> eyre::Result<Export> {
+        if let Some(dir) = dir {
+            fs::create_dir_all(dir)
+                .wrap_err(format!(
+                    "Failed to create directory '{}'",
+                    dir.to_string_lossy()
+                ))
+                .suggestion(
+                    "Please check the path and the permissions of the --export-fixes folder",
+                )?;
+        }
+
+        // the files of the translation units must not be placed in the export directory since
+        // clang-apply-replacements would pick them up as well. The directory must not exist,
+        // such that no stale files of another run, e.g., with a reused process id, are merged
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let nanos = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let tmp = std::env::temp_dir().join(format!(
+            "run-clang-tidy-fixes-{}-{nanos}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&tmp).wrap_err(format!(
+            "Failed to create temporary directory '{}'",
+            tmp.to_string_lossy()
+        ))?;
+
+        Ok(Export {
+            dir: dir.map(path::Path::to_path_buf),
+            tmp,
+        })
+    }
+
+    /// Provides the file passed to `clang-tidy` using `-export-fixes` for the item `index`.
+    pub fn file(&self, index: usize) -> path::PathBuf {
+        self.tmp.join(format!("{index}.yaml"))
+    }
+
+    /// Merges the files of the first `count` items. Files that do not exist are skipped, e.g.,
+    /// if `clang-tidy` crashed.
+    fn collect(&self, count: usize) -> eyre::Result<Replacements> {
+        let mut merged = Replacements::default();
+        let mut known = HashSet::new();
+
+        for index in 0..count {
+            let file = self.file(index);
+            let content = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            let replacements: Replacements = serde_norway::from_str(&content)
+                .wrap_err(format!(
+                    "Failed to read exported fixes '{}'",
+                    file.to_string_lossy()
+                ))
+                .suggestion(
+                    "Please make sure that the clang-tidy command supports -export-fixes",
+                )?;
+
+            for diagnostic in replacements.diagnostics {
+                if known.insert(key(&diagnostic)) {
+                    merged.diagnostics.push(diagnostic);
+                }
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Writes the merged fixes of the first `count` items to the replacements file and provides
+    /// its path and the number of diagnostics. Fails if no directory has been provided.
+    pub fn write(&self, count: usize) -> eyre::Result<(path::PathBuf, usize)> {
+        let dir = self
+            .dir
+            .as_ref()
+            .ok_or(eyre!(
+                "No directory has been provided for exporting the fixes"
+            ))
+            .suggestion("Please use --export-fixes to specify the directory")?;
+        let merged = self.collect(count)?;
+
+        let file = dir.join(FILE_NAME);
+        let content = serde_norway::to_string(&merged).unwrap();
+        fs::write(&file, format!("---\n{content}...\n"))
+            .wrap_err(format!("Failed to write '{}'", file.to_string_lossy()))
+            .suggestion(format!(
+                "Please check the permissions for the folder {}",
+                dir.to_string_lossy()
+            ))?;
+        Ok((file, merged.diagnostics.len()))
+    }
+
+    /// Resolves the merged fixes of the first `count` items to the new contents of all files
+    /// that are modified. Fixes that conflict with a previous fix are skipped.
+    fn changes(&self, count: usize) -> eyre::Result<(Vec<Change>, Applied)> {
+        let fixes: Vec<Fix> = self
+            .collect(count)?
+            .diagnostics
+            .into_iter()
+            .filter_map(|diagnostic| match serde_norway::from_value(diagnostic) {
+                Ok(fix) => Some(fix),
+                Err(err) => {
+                    log::debug!("Ignoring invalid diagnostic in exported fixes: {err}");
+                    None
+                }
+            })
+            .collect();
+
+        let (accepted, mut conflicts) = select(&fixes);
+        let fixes = accepted
+            .iter()
+            .map(|(_, fix)| *fix)
+            .collect::<HashSet<_>>()
+            .len();
+
+        let mut files: BTreeMap<&path::Path, Vec<&Replacement>> = BTreeMap::new();
+        for (replacement, _) in accepted.iter() {
+            files
+                .entry(replacement.file_path.as_path())
+                .or_default()
+                .push(replacement);
+        }
+
+        let mut changes = vec![];
+        for (file, replacements) in files {
+            let name = file.to_string_lossy();
+            let old = fs::read(file).wrap_err(format!("Failed to read '{name}'"))?;
+            match apply_to(old.clone(), replacements) {
+                Some(new) => changes.push(Change {
+                    file: file.to_path_buf(),
+                    old,
+                    new,
+                }),
+                None => conflicts.push(format!(
+                    "{name}: the fixes exceed the file, it has been modified during the analysis"
+                )),
+            }
+        }
+
+        let applied = Applied {
+            fixes,
+            files: changes.iter().map(|change| change.file.clone()).collect(),
+            conflicts,
+        };
+        Ok((changes, applied))
+    }
+
+    /// Applies the merged fixes of the first `count` items. Fixes that conflict with a previous
+    /// fix are skipped, all files are modified only once all fixes have been resolved.
+    pub fn apply(&self, count: usize) -> eyre::Result<Applied> {
+        let (changes, applied) = self.changes(count)?;
+        for change in changes {
+            let name = change.file.to_string_lossy();
+            write_atomic(&change.file, &change.new)
+                .wrap_err(format!("Failed to apply fixes to '{name}'"))
+                .suggestion(format!("Please check the permissions of the file {name}"))?;
+        }
+        Ok(applied)
+    }
+
+    /// Provides the merged fixes of the first `count` items as unified diff without modifying
+    /// any file. Paths within the directory `base` are relative, such that the diff can be
+    /// applied using `git apply` or `patch -p1` from within `base`.
+    pub fn diff(&self, count: usize, base: &path::Path) -> eyre::Result<(String, Applied)> {
+        let (changes, applied) = self.changes(count)?;
+        let diff = changes
+            .iter()
+            .map(|change| change.diff(base))
+            .collect::<Vec<_>>()
+            .concat();
+        Ok((diff, applied))
+    }
+}
+
+/// New content of a file that is modified by the fixes.
+struct Change {
+    file: path::PathBuf,
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+impl Change {
+    fn diff(&self, base: &path::Path) -> String {
+        let (from, to) = match self.file.strip_prefix(base) {
+            Ok(rel) => {
+                let rel = rel.to_string_lossy().replace('\\', "/");
+                (format!("a/{rel}"), format!("b/{rel}"))
+            }
+            Err(_) => {
+                let name = self.file.to_string_lossy().to_string();
+                (name.clone(), name)
+            }
+        };
+
+        let old = String::from_utf8_lossy(&self.old);
+        let new = String::from_utf8_lossy(&self.new);
+        let old: Vec<_> = old.split_inclusive('\n').map(line).collect();
+        let new: Vec<_> = new.split_inclusive('\n').map(line).collect();
+
+        difflib::unified_diff(&old, &new, &from, &to, "", "", 3)
+            .into_iter()
+            .map(|line| match line.strip_suffix("\t\n") {
+                // the headers are followed by an empty date
+                Some(header) if line.starts_with("--- ") || line.starts_with("+++ ") => {
+                    format!("{header}\n")
+                }
+                _ => line,
+            })
+            .collect()
+    }
+}
+
+/// Terminates the last line of a file that does not end with a newline, as expected by the
+/// unified diff format.
+fn line(line: &str) -> String {
+    match line.ends_with('\n') {
+        true => line.to_string(),
+        false => format!("{line}\n\\ No newline at end of file\n"),
+    }
+}
+
+impl Drop for Export {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.tmp);
+    }
+}
+
+/// Identifies duplicate diagnostics: A header that is included by multiple translation units
+/// leads to the same diagnostic for each of them, only the build directory may differ.
+fn key(diagnostic: &serde_norway::Value) -> serde_norway::Value {
+    let mut key = diagnostic.clone();
+    if let Some(mapping) = key.as_mapping_mut() {
+        mapping.remove("BuildDirectory");
+    }
+    key
+}
+
+/// Selects the replacements of all fixes that do not conflict with a previous fix, each
+/// replacement is provided with the index of its fix. Replacements that are contained in multiple
+/// fixes are only selected once.
+fn select(fixes: &[Fix]) -> (Vec<(Replacement, usize)>, Vec<String>) {
+    let mut accepted: Vec<(Replacement, usize)> = vec![];
+    let mut conflicts = vec![];
+
+    for (index, fix) in fixes.iter().enumerate() {
+        let replacements: Vec<_> = fix
+            .replacements()
+            .into_iter()
+            .filter(|replacement| accepted.iter().all(|(known, _)| known != replacement))
+            .collect();
+
+        let conflict = replacements.iter().find_map(|replacement| {
+            accepted
+                .iter()
+                .find(|(known, _)| known.overlaps(replacement))
+                .map(|(_, other)| (replacement, &fixes[*other]))
+        });
+        match conflict {
+            Some((replacement, other)) => conflicts.push(format!(
+                "{}: fix of '{}' at offset {} conflicts with the fix of '{}'",
+                replacement.file_path.to_string_lossy(),
+                fix.diagnostic_name,
+                replacement.offset,
+                other.diagnostic_name
+            )),
+            None => accepted.extend(replacements.into_iter().map(|r| (r, index))),
+        }
+    }
+    (accepted, conflicts)
+}
+
+/// Applies the non-overlapping `replacements` to the `content`, fails if any replacement exceeds
+/// the content.
+fn apply_to(mut content: Vec<u8>, mut replacements: Vec<&Replacement>) -> Option<Vec<u8>> {
+    // replacements are applied back to front such that the offsets remain valid
+    replacements.sort_by_key(|replacement| std::cmp::Reverse(replacement.offset));
+    for replacement in replacements {
+        if replacement.end() > content.len() {
+            return None;
+        }
+        content.splice(
+            replacement.offset..replacement.end(),
+            replacement.replacement_text.bytes(),
+        );
+    }
+    Some(content)
+}
+
+/// Replaces the `file` by writing to a temporary file first, such that the file is never left
+/// partially written.
+fn write_atomic(file: &path::Path, content: &[u8]) -> std::io::Result<()> {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = file.with_file_name(format!(".{name}.{}.tmp", std::process::id()));
+
+    let written = fs::write(&tmp, content)
+        .and_then(|_| fs::set_permissions(&tmp, fs::metadata(file)?.permissions()))
+        .and_then(|_| fs::rename(&tmp, file));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_tmp() {
+        // each export uses its own temporary directory, which is removed when dropped
+        let export = Export::new(None).unwrap();
+        let other = Export::new(None).unwrap();
+        assert_ne!(export.tmp, other.tmp);
+        assert!(export.tmp.is_dir());
+
+        let tmp = export.tmp.clone();
+        drop(export);
+        assert!(!tmp.exists());
+    }
+
+    #[test]
+    fn test_write_without_dir() {
+        // fixes that are only applied cannot be exported
+        let export = Export::new(None).unwrap();
+        assert!(export.write(0).is_err());
+    }
+
+    #[test]
+    fn test_key() {
+        let diagnostic = |dir: &str| {
+            serde_norway::from_str::<serde_norway::Value>(&format!(
+                "DiagnosticName: bugprone-macro-parentheses\n\
+                 DiagnosticMessage:\n  \
+                   Message: macro argument should be enclosed in parentheses\n  \
+                   FilePath: module_fix.h\n  \
+                   FileOffset: 180\n\
+                 BuildDirectory: {dir}\n"
+            ))
+            .unwrap()
+        };
+        assert_eq!(key(&diagnostic("a")), key(&diagnostic("b")));
+        assert_ne!(diagnostic("a"), diagnostic("b"));
+    }
+
+    #[test]
+    fn test_select_and_apply() {
+        let fix = |name: &str, replacements: &[(usize, usize, &str)]| Fix {
+            diagnostic_name: name.to_string(),
+            diagnostic_message: Message {
+                replacements: replacements
+                    .iter()
+                    .map(|(offset, length, text)| Replacement {
+                        file_path: path::PathBuf::from("/does-not-exist/a.h"),
+                        offset: *offset,
+                        length: *length,
+                        replacement_text: text.to_string(),
+                    })
+                    .collect(),
+            },
+            build_directory: path::PathBuf::from("/does-not-exist"),
+        };
+
+        let fixes = [
+            fix("parentheses", &[(0, 0, "("), (5, 0, ")")]),
+            // the same fix, e.g., reported for another translation unit
+            fix("parentheses", &[(0, 0, "("), (5, 0, ")")]),
+            fix("rename", &[(4, 2, "xyz")]),
+            fix("insert", &[(0, 0, "#")]),
+            fix("append", &[(6, 0, ";")]),
+        ];
+
+        let (accepted, conflicts) = select(&fixes);
+        assert_eq!(
+            vec![0, 0, 4],
+            accepted.iter().map(|(_, i)| *i).collect::<Vec<_>>()
+        );
+        assert_eq!(2, conflicts.len());
+        assert!(conflicts[0].contains("'rename'"));
+
+        let replacements = accepted.iter().map(|(r, _)| r).collect();
+        assert_eq!(
+            Some(b"(a + b) ;".to_vec()),
+            apply_to(b"a + b ".to_vec(), replacements)
+        );
+        assert_eq!(None, apply_to(b"a".to_vec(), vec![&accepted[1].0]));
+    }
+
+    #[test]
+    fn test_diff() {
+        let change = Change {
+            file: path::PathBuf::from("/src/a.h"),
+            old: b"int a;\nint b = a + b".to_vec(),
+            new: b"int a;\nint b = (a + b)".to_vec(),
+        };
+        assert_eq!(
+            "--- a/a.h\n\
+             +++ b/a.h\n\
+             @@ -1,2 +1,2 @@\n \
+             int a;\n\
+             -int b = a + b\n\\ No newline at end of file\n\
+             +int b = (a + b)\n\\ No newline at end of file\n",
+            change.diff(path::Path::new("/src"))
+        );
+    }
+}
@@ -82,3 +82,46 @@ where
         .collect(); // accept only files
     (paths, filtered)
 }
+
+fn is_any_match(globs: &[globmatch::GlobSet<'_>], path: &path::Path) -> bool {
+    globs.iter().any(|glob| glob.is_match(path))
+}
+
+/// Applies the filters to a list of paths that have not been resolved by matching globs, e.g.,
+/// the files listed in the `compile_commands.json`.
+///
+/// The pre-filter is applied to the path and all of its parent directories within the `root`,
+/// mimicking the recursive search of `match_paths`. For paths outside the root it is only applied
+/// to the path itself. Returns the remaining and the filtered paths.
+pub fn filter_paths(
+    paths: Vec<path::PathBuf>,
+    root: &path::Path,
+    filter_pre: &Option<Vec<globmatch::GlobSet<'_>>>,
+    filter_post: &Option<Vec<globmatch::GlobSet<'_>>>,
+) -> (Vec<path::PathBuf>, Vec<path::PathBuf>) {
+    let is_pre_filtered = |path: &path::Path| match filter_pre {
+        Some(globs) => is_any_match(globs, path),
+        None => globmatch::is_hidden_entry(path),
+    };
+
+    let (paths, mut filtered): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .filter(|path| {
+            let mut entries = path
+                .ancestors()
+                .take_while(|dir| dir.starts_with(root) && *dir != root);
+            // entries that are filtered before matching are not listed as filtered paths
+            if path.starts_with(root) {
+                !entries.any(is_pre_filtered)
+            } else {
+                !is_pre_filtered(path)
+            }
+        })
+        .partition(|path| match filter_post {
+            None => true,
+            Some(globs) => !is_any_match(globs, path),
+        });
+
+    filtered.sort_unstable();
+    (paths, filtered)
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, path,
+};
+
+use crate::compdb;
+
+/// Include directive `#include "name"` (quoted) or `#include <name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    quoted: bool,
+    name: String,
+}
+
+fn directives(content: &str) -> Vec<Directive> {
+    let re = regex::Regex::new(r#"^\s*#\s*(?:include_next|include|import)\s*([<"])([^>"]+)[>"]"#)
+        .unwrap();
+
+    content
+        .lines()
+        .filter_map(|line| re.captures(line))
+        .map(|captures| Directive {
+            quoted: &captures[1] == "\"",
+            name: captures[2].to_string(),
+        })
+        .collect()
+}
+
+/// Resolves the files included by translation units.
+///
+/// The scanner does not evaluate the preprocessor: Includes within conditional blocks are always
+/// followed, such that the resolved set of files is a superset of the actually included files.
+/// Includes that cannot be resolved using the include directories of the compile command, e.g.,
+/// the headers of the standard library, are ignored.
+pub struct Scanner {
+    directives: HashMap<path::PathBuf, Vec<Directive>>,
+}
+
+impl Scanner {
+    pub fn new() -> Scanner {
+        Scanner {
+            directives: HashMap::new(),
+        }
+    }
+
+    fn directives_of(&mut self, file: &path::Path) -> &[Directive] {
+        self.directives
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                fs::read(file)
+                    .map(|content| directives(&String::from_utf8_lossy(&content)))
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Provides the canonical paths of all files that are (transitively) included by the
+    /// translation unit of the compile command `entry`.
+    pub fn closure(&mut self, entry: &compdb::Entry) -> HashSet<path::PathBuf> {
+        let (quote_dirs, angle_dirs) = entry.include_dirs();
+        let mut included = HashSet::new();
+        let mut pending = vec![entry.file.clone()];
+
+        while let Some(file) = pending.pop() {
+            let parent = file.parent().map(path::Path::to_path_buf);
+
+            for directive in self.directives_of(&file).to_vec() {
+                let dirs = if directive.quoted {
+                    // quoted includes are first searched relative to the including file
+                    parent.iter().chain(quote_dirs.iter()).collect::<Vec<_>>()
+                } else {
+                    angle_dirs.iter().collect()
+                };
+
+                let resolved = dirs
+                    .into_iter()
+                    .map(|dir| dir.join(&directive.name))
+                    .find(|candidate| candidate.is_file())
+                    .and_then(|candidate| candidate.canonicalize().ok());
+
+                if let Some(resolved) = resolved {
+                    if resolved != entry.file && included.insert(resolved.clone()) {
+                        pending.push(resolved);
+                    }
+                }
+            }
+        }
+        included
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        let content = r#"
+            #include "module_a.h"
+            # include <stdio.h>
+            // #include "commented.h"
+            #define INCLUDE "not_included.h"
+            #import "imported.h"
+        "#;
+
+        let names: Vec<_> = directives(content)
+            .into_iter()
+            .map(|d| (d.quoted, d.name))
+            .collect();
+        assert_eq!(
+            vec![
+                (true, "module_a.h".to_string()),
+                (false, "stdio.h".to_string()),
+                (true, "imported.h".to_string()),
+            ],
+            names
+        );
+    }
+}
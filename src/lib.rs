@@ -1,14 +1,25 @@
-use std::{fs, path};
+use std::{
+    fs, path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 #[allow(unused_imports)]
 use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 use serde::Deserialize;
 
 pub mod cli;
 pub mod cmd;
 
+mod baseline;
+mod cache;
+mod changes;
+mod compdb;
+mod fixes;
 mod globs;
+mod includes;
+mod profile;
+mod report;
 mod resolve;
 
 #[derive(Deserialize, Debug)]
@@ -20,8 +31,14 @@ pub struct JsonModel {
 }
 
 enum Dump {
-    Error { msg: String, path: path::PathBuf },
-    Warning { msg: String, path: path::PathBuf },
+    Error {
+        output: cmd::Output,
+        path: path::PathBuf,
+    },
+    Warning {
+        output: cmd::Output,
+        path: path::PathBuf,
+    },
 }
 
 fn log_pretty() -> bool {
@@ -53,8 +70,8 @@ impl LogStep {
     }
 }
 
-fn get_command(data: &cli::Data) -> eyre::Result<cmd::Runner> {
-    let cmd_path = resolve::command(data)?;
+fn get_command(data: &cli::Data, json: &cli::JsonModel) -> eyre::Result<cmd::Runner> {
+    let cmd_path = resolve::command(data, json)?;
     let mut cmd = cmd::Runner::new(&cmd_path);
 
     cmd.validate()
@@ -70,8 +87,46 @@ fn get_command(data: &cli::Data) -> eyre::Result<cmd::Runner> {
     Ok(cmd)
 }
 
+/// Tidy file and the root folder it is copied to.
+type TidyPlacement = Option<(path::PathBuf, path::PathBuf)>;
+
+/// Determines how the tidy file is passed to `clang-tidy`: Versions that support the option
+/// `--config-file` use the tidy file directly (.0), older versions require the tidy file to be
+/// copied to the root folder (.1).
+fn tidy_strategy(
+    tidy_and_root: Option<(path::PathBuf, Option<path::PathBuf>)>,
+    cmd: &cmd::Runner,
+) -> eyre::Result<(Option<path::PathBuf>, TidyPlacement)> {
+    let (tidy_file, tidy_root) = match tidy_and_root {
+        None => return Ok((None, None)),
+        Some(tidy_and_root) => tidy_and_root,
+    };
+
+    match (cmd.supports_config_file(), tidy_root) {
+        (Ok(_), tidy_root) => {
+            if tidy_root.is_some() {
+                log::debug!("Passing tidy file using --config-file, 'tidyRoot' is not used");
+            }
+            Ok((Some(tidy_file), None))
+        }
+        (Err(err), Some(tidy_root)) => {
+            log::debug!("{err}, copying the tidy file to 'tidyRoot' instead");
+            Ok((None, Some((tidy_file, tidy_root))))
+        }
+        (Err(err), None) => Err(eyre!(err))
+            .wrap_err(format!(
+                "Found tidy file '{}' but could not find root folder configuration",
+                tidy_file.to_string_lossy()
+            ))
+            .suggestion(
+                "Please add the field 'tidyRoot' to your configuration file \
+                 or use clang-tidy version 12.0.0 or higher.",
+            ),
+    }
+}
+
 fn place_tidy_file(
-    file_and_root: Option<(path::PathBuf, path::PathBuf)>,
+    file_and_root: TidyPlacement,
     step: &mut LogStep,
 ) -> eyre::Result<Option<path::PathBuf>> {
     if file_and_root.is_none() {
@@ -143,6 +198,32 @@ fn place_tidy_file(
     Ok(Some(dst_file))
 }
 
+fn setup_cache(
+    data: &cli::Data,
+    cmd: &cmd::Runner,
+    build_root: &path::Path,
+    database: &Result<compdb::Database, String>,
+) -> eyre::Result<Option<cache::Cache>> {
+    let dir = match &data.cache_dir {
+        None => return Ok(None),
+        Some(dir) => dir,
+    };
+    if data.fix || data.export_fixes.is_some() {
+        // a cached result would skip applying or exporting the fixes
+        log::warn!("The cache is not used when applying or exporting fixes");
+        return Ok(None);
+    }
+    if let Err(err) = database {
+        log::warn!("Compile commands are not considered for caching: {err}");
+    }
+
+    let options = vec![
+        format!("build-root={}", build_root.to_string_lossy()),
+        format!("suppress-warnings={}", data.ignore_warn),
+    ];
+    Ok(Some(cache::Cache::new(dir, cmd, &options)?))
+}
+
 fn setup_jobs(jobs: Option<u8>) -> eyre::Result<()> {
     // configure rayon to use the specified number of threads (globally)
     if let Some(jobs) = jobs {
@@ -160,19 +241,273 @@ fn setup_jobs(jobs: Option<u8>) -> eyre::Result<()> {
     Ok(())
 }
 
-pub fn run(data: cli::Data) -> eyre::Result<()> {
-    let start = std::time::Instant::now();
+/// Provides the canonical paths of all files to analyze and the filtered paths.
+fn collect_paths(
+    json: &cli::JsonModel,
+    source: cli::Source,
+    database: &Result<compdb::Database, String>,
+) -> eyre::Result<(Vec<path::PathBuf>, Vec<path::PathBuf>)> {
+    let filter_pre = globs::build_glob_set_from(&json.filter_pre, "preFilter", &json.name)?;
+    let filter_post = globs::build_glob_set_from(&json.filter_post, "postFilter", &json.name)?;
 
-    log::info!(" ");
-    let mut step = LogStep::new();
+    let (mut paths, mut filtered) = match source {
+        cli::Source::Paths => (vec![], vec![]),
+        cli::Source::CompileCommands | cli::Source::Both => {
+            let database = database
+                .as_ref()
+                .map_err(|err| eyre!(err.clone()))
+                .wrap_err("Failed to read the files to analyze")
+                .suggestion(format!(
+                    "The field 'source' in {} requires a compile_commands.json in the build root",
+                    json.name
+                ))?;
+            globs::filter_paths(database.files(), &json.root, &filter_pre, &filter_post)
+        }
+    };
+
+    if let (cli::Source::Paths | cli::Source::Both, Some(_)) = (source, &json.paths) {
+        let patterns = json.globs();
+        let candidates = globs::build_matchers_from(&patterns, &json.root, "paths", &json.name)?;
+        let (matched, matched_filtered) = globs::match_paths(candidates, filter_pre, filter_post);
+
+        paths.extend(matched.into_iter().map(|p| p.canonicalize().unwrap()));
+        filtered.extend(matched_filtered);
+        paths.sort_unstable();
+        paths.dedup();
+    }
+    Ok((paths, filtered))
+}
+
+/// Checks that all `paths` have an entry in the compilation database. Depending on the `mode`,
+/// files without a compile command are removed, reported or lead to an error. The files are
+/// not checked if no `mode` is provided.
+fn check_commands(
+    paths: Vec<path::PathBuf>,
+    database: &Result<compdb::Database, String>,
+    mode: Option<cli::MissingCommands>,
+) -> eyre::Result<Vec<path::PathBuf>> {
+    let mode = match mode {
+        Some(mode) if !paths.is_empty() => mode,
+        _ => return Ok(paths),
+    };
+    let database = match (database, mode) {
+        (Ok(database), _) => database,
+        (Err(err), cli::MissingCommands::Warn) => {
+            log::warn!("Unable to check the files for missing compile commands: {err}");
+            return Ok(paths);
+        }
+        (Err(err), _) => {
+            return Err(eyre!(err.clone()))
+                .wrap_err("Unable to check the files for missing compile commands")
+                .suggestion(
+                    "Please make sure that the build root contains a compile_commands.json",
+                )
+        }
+    };
+
+    let (paths, missing): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .partition(|path| database.entry(path).is_some());
+    if missing.is_empty() {
+        return Ok(paths);
+    }
+
+    let list = missing
+        .iter()
+        .map(|path| format!("  {}", path.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match mode {
+        cli::MissingCommands::Skip => {
+            log::debug!("Files without compile command:\n{}", list);
+            Ok(paths)
+        }
+        cli::MissingCommands::Warn => {
+            log::warn!(
+                "\n\nThe following files have no compile command, clang-tidy will guess the \
+                 compile flags:\n\n{}\n",
+                list
+            );
+            let mut paths = paths;
+            paths.extend(missing);
+            paths.sort_unstable();
+            Ok(paths)
+        }
+        cli::MissingCommands::Fail => Err(eyre!(
+            "The following files have no compile command:\n{}\n",
+            list
+        ))
+        .suggestion(
+            "Exclude the files using the field 'filterPost' or use the option \
+             '--missing-commands skip' to skip files without compile command",
+        ),
+    }
+}
+
+/// Selects all `paths` that have been changed since the git revision `rev`, or include a file
+/// that has been changed.
+fn select_changed(
+    paths: Vec<path::PathBuf>,
+    rev: &str,
+    root: &path::Path,
+    database: &Result<compdb::Database, String>,
+) -> eyre::Result<Vec<path::PathBuf>> {
+    let changed = changes::since(root, rev)?;
+
+    // the include closure is resolved for all paths, since also changed files that are matched
+    // themselves, e.g., headers, affect the files including them
+    let database = match database {
+        Ok(database) => Some(database),
+        Err(err) => {
+            log::warn!(
+                "Failed to load the compilation database, includes are only resolved relative \
+                 to the including file: {err}"
+            );
+            None
+        }
+    };
+    let mut scanner = includes::Scanner::new();
+
+    Ok(paths
+        .into_iter()
+        .filter(|path| {
+            if changed.contains(path) {
+                return true;
+            }
+            let closure = match database.and_then(|database| database.entry(path)) {
+                Some(entry) => scanner.closure(entry),
+                None => {
+                    log::debug!(
+                        "No compile command found for '{}', only resolving includes \
+                         relative to the file",
+                        path.to_string_lossy()
+                    );
+                    scanner.closure(&compdb::Entry {
+                        directory: path.parent().unwrap().to_path_buf(),
+                        file: path.clone(),
+                        arguments: vec![],
+                    })
+                }
+            };
+            closure.iter().any(|file| changed.contains(file))
+        })
+        .collect())
+}
+
+fn apply_baseline(
+    baseline: &cli::Baseline,
+    known: Option<baseline::Baseline>,
+    records: Vec<report::Record>,
+    roots: &[&path::Path],
+) -> eyre::Result<Vec<report::Record>> {
+    let file_name = baseline.file.to_string_lossy();
 
-    let tidy_and_root = resolve::tidy_and_root(&data)?;
+    let known = match known {
+        Some(known) => known,
+        None => {
+            let count = baseline::update(&baseline.file, &records, roots)?;
+            log::info!(
+                "Recorded {} findings in baseline {}",
+                console::style(count).bold(),
+                console::style(&file_name).bold()
+            );
+            baseline::Baseline::load(&baseline.file)?
+        }
+    };
+
+    let (records, applied) = baseline::apply(known, records, roots);
+    if applied.suppressed > 0 {
+        log::info!(
+            "Suppressed {} known findings using baseline {}",
+            console::style(applied.suppressed).bold(),
+            console::style(&file_name).bold()
+        );
+    }
+    if !applied.fixed.is_empty() {
+        log::warn!(
+            "\n\nThe following findings of the baseline {} have been fixed:\n\n{}\n\n\
+             Execute 'baseline update' to remove them from the baseline.\n",
+            file_name,
+            applied.fixed.join("\n")
+        );
+    }
+    Ok(records)
+}
+
+fn log_fixed_files(files: &[path::PathBuf]) {
+    if !files.is_empty() {
+        log::info!(
+            "Files with fixes:\n{}",
+            files
+                .iter()
+                .map(|file| format!("  {}", file.to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
+
+fn log_conflicts(conflicts: &[String]) {
+    if !conflicts.is_empty() {
+        log::warn!(
+            "\n\nThe following fixes have not been applied since they conflict with \
+             other fixes:\n\n{}\n\nExecute the tool again to apply the remaining fixes.\n",
+            conflicts.join("\n")
+        );
+    }
+}
+
+/// Tidy file that has been placed in the tidy root, removed when the guard goes out of scope.
+type TidyGuard = scopeguard::ScopeGuard<Option<path::PathBuf>, fn(Option<path::PathBuf>)>;
+
+fn remove_tidy_file(path: Option<path::PathBuf>) {
+    // ensure we delete the temporary tidy file at return or panic
+    if let Some(path) = path {
+        let str = format!("Cleaning up temporary file {}\n", path.to_string_lossy());
+        let str = console::style(str).dim().italic();
+
+        log::info!("\n{}", str);
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Configuration file that has been prepared for executing `clang-tidy`.
+struct Unit<'a> {
+    json: &'a cli::JsonModel,
+    build_root: path::PathBuf,
+    database: Result<compdb::Database, String>,
+    /// Canonical paths of all files to analyze.
+    paths: Vec<path::PathBuf>,
+    filtered: Vec<path::PathBuf>,
+    cmd: cmd::Runner,
+    cmd_path: path::PathBuf,
+    /// Resolved tidy file and root, used for reports.
+    tidy_info: Option<(path::PathBuf, Option<path::PathBuf>)>,
+    strip_root: Option<path::PathBuf>,
+    profiles: profile::Profiles,
+    cache: Option<cache::Cache>,
+}
+
+fn prepare<'a>(
+    data: &cli::Data,
+    json: &'a cli::JsonModel,
+    step: &mut LogStep,
+    placed: &mut Vec<TidyGuard>,
+) -> eyre::Result<Unit<'a>> {
+    let tidy_and_root = resolve::tidy_and_root(data, json)?;
     if let Some((tidy_file, _)) = &tidy_and_root {
         log::info!(
             "{} Found tidy file {}",
             step.next(),
             console::style(tidy_file.to_string_lossy()).bold(),
         );
+    } else if json.tidy_config.is_some() {
+        log::info!(
+            "{} Using the tidy configuration of {}",
+            step.next(),
+            console::style(&json.name).bold(),
+        );
     } else {
         // no tidy file specified, it'll be picked by `clang-tidy` itself as the first `.clang-tidy`
         // file that is encountered when walking all parent paths recursively.
@@ -182,37 +517,61 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         );
     }
 
-    let build_root = resolve::build_root(&data)?;
+    let build_root = resolve::build_root(data, json)?;
     log::info!(
         "{} Using build root {}",
         step.next(),
         console::style(build_root.to_string_lossy()).bold(),
     );
 
-    let candidates =
-        globs::build_matchers_from(&data.json.paths, &data.json.root, "paths", &data.json.name)?;
-    let filter_pre =
-        globs::build_glob_set_from(&data.json.filter_pre, "preFilter", &data.json.name)?;
-    let filter_post =
-        globs::build_glob_set_from(&data.json.filter_post, "postFilter", &data.json.name)?;
+    let source = json.source.unwrap_or(cli::Source::Paths);
+    let database = compdb::Database::load(&build_root);
+    let (paths, filtered) = collect_paths(json, source, &database)?;
 
-    let (paths, filtered) = globs::match_paths(candidates, filter_pre, filter_post);
-    let paths = paths.into_iter().map(|p| p.canonicalize().unwrap());
+    let mut info = vec![];
+    if !filtered.is_empty() {
+        info.push(format!("filtered {} paths", filtered.len()));
+    }
+
+    let paths = match &data.changed_since {
+        None => paths,
+        Some(rev) => {
+            let count = paths.len();
+            let paths = select_changed(paths, rev, &json.root, &database)?;
+            info.push(format!("{} unchanged since '{rev}'", count - paths.len()));
+            paths
+        }
+    };
+
+    let count = paths.len();
+    let paths = check_commands(paths, &database, data.missing_commands)?;
+    if paths.len() != count {
+        info.push(format!(
+            "skipped {} without compile command",
+            count - paths.len()
+        ));
+    }
 
-    let filtered = if filtered.is_empty() {
+    let filtered_info = if info.is_empty() {
         "".to_string()
     } else {
-        format!(" (filtered {} paths)", filtered.len())
+        format!(" ({})", info.join(", "))
     };
 
     log::info!(
-        "{} Found {} files for the provided path patterns{}",
+        "{} Found {} files {}{}",
         step.next(),
         console::style(paths.len()).bold(),
-        filtered
+        match source {
+            cli::Source::Paths => "for the provided path patterns",
+            cli::Source::CompileCommands => "in the compile commands",
+            cli::Source::Both => "for the provided path patterns and compile commands",
+        },
+        filtered_info
     );
 
-    let cmd = get_command(&data)?;
+    let mut cmd = get_command(data, json)?;
+    cmd.set_timeout(json.timeout.map(std::time::Duration::from_secs));
     let cmd_path = match cmd.get_path().canonicalize() {
         Ok(path) => path,
         Err(_) => cmd.get_path(),
@@ -224,29 +583,161 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         console::style(cmd_path.to_string_lossy()).bold(),
     );
 
-    let strip_root = if let Some((_, tidy_root)) = &tidy_and_root {
-        Some(path::PathBuf::from(tidy_root.as_path()))
-    } else {
-        None
-    };
+    // the resolved tidy file and root are moved when placing the tidy file but needed for reports
+    let tidy_info = tidy_and_root.clone();
+    let strip_root = tidy_and_root
+        .as_ref()
+        .and_then(|(_, tidy_root)| tidy_root.clone());
+
+    let (config_file, placement) = tidy_strategy(tidy_and_root, &cmd)?;
+    let tidy = place_tidy_file(placement, step)?;
+    placed.push(scopeguard::guard(tidy, remove_tidy_file as fn(_)));
+
+    let profiles = profile::Profiles::new(data, json, config_file, &cmd, &paths)?;
+    let cache = setup_cache(data, &cmd, &build_root, &database)?;
+
+    Ok(Unit {
+        json,
+        build_root,
+        database,
+        paths,
+        filtered,
+        cmd,
+        cmd_path,
+        tidy_info,
+        strip_root,
+        profiles,
+        cache,
+    })
+}
 
-    let tidy = place_tidy_file(tidy_and_root, &mut step)?;
-    // binding for scope guard is not used, but an action needed when the variable goes out of scope
-    let _tidy = scopeguard::guard(tidy, |path| {
-        // ensure we delete the temporary tidy file at return or panic
-        if let Some(path) = path {
-            let str = format!("Cleaning up temporary file {}\n", path.to_string_lossy());
-            let str = console::style(str).dim().italic();
+/// Performs all steps of a run except for executing `clang-tidy` and reports the resolved values
+/// and files of each configuration. All configurations are checked, even if some are invalid.
+fn check_config(data: cli::Data) -> eyre::Result<()> {
+    let mut data = data;
+    let jsons = std::mem::take(&mut data.jsons);
+    let count = jsons.len();
+    let mut invalid = vec![];
 
-            log::info!("\n{}", str);
-            let _ = fs::remove_file(path);
+    for (index, json) in jsons.into_iter().enumerate() {
+        log::info!(" ");
+        log::info!(
+            "Checking configuration {} ({}/{})",
+            console::style(&json.name).bold(),
+            index + 1,
+            count
+        );
+        let name = json.name.clone();
+        let mut step = LogStep::new();
+        let checked = resolve::config(&data, json).and_then(|json| {
+            // placed tidy files are removed when the guards are dropped
+            let mut placed = vec![];
+            let unit = prepare(&data, &json, &mut step, &mut placed)?;
+            log_unit(&unit);
+            Ok(())
+        });
+
+        if let Err(err) = checked {
+            if count == 1 {
+                return Err(err);
+            }
+            log::error!("{err:#}");
+            invalid.push(name);
         }
-    });
+    }
+
+    log::info!(" ");
+    if !invalid.is_empty() {
+        return Err(eyre!(
+            "Found {} invalid configuration(s): {}",
+            invalid.len(),
+            invalid.join(", ")
+        ))
+        .suggestion("Execute 'check-config' for each of the configurations to see all details");
+    }
+    log::info!(
+        "{} configuration(s) are valid",
+        console::style(count).green().bold()
+    );
+    Ok(())
+}
+
+/// Logs the resolved values and files of a prepared configuration.
+fn log_unit(unit: &Unit<'_>) {
+    if let Some((_, Some(tidy_root))) = &unit.tidy_info {
+        log::info!("Tidy root: {}", tidy_root.to_string_lossy());
+    }
+    log::info!("Build root: {}", unit.build_root.to_string_lossy());
+    log::info!("Command: {}", unit.cmd_path.to_string_lossy());
+    match &unit.database {
+        Ok(_) => log::info!("Compile commands: found"),
+        Err(err) => log::info!("Compile commands: {err}"),
+    }
+    if !unit.filtered.is_empty() {
+        log::info!("Filtered paths: {}", unit.filtered.len());
+    }
+
+    log::info!("Files to analyze: {}", unit.paths.len());
+    for path in unit.paths.iter() {
+        log::info!("  {}", strip_path(path, &unit.strip_root).to_string_lossy());
+    }
+}
+
+pub fn run(data: cli::Data) -> eyre::Result<()> {
+    if data.check {
+        return check_config(data);
+    }
+    let start = std::time::Instant::now();
+
+    let mut data = data;
+    data.jsons = std::mem::take(&mut data.jsons)
+        .into_iter()
+        .map(|json| resolve::config(&data, json))
+        .collect::<eyre::Result<_>>()?;
+
+    log::info!(" ");
+    let mut step = LogStep::new();
+
+    // known findings are loaded before executing clang-tidy to fail early for invalid baselines
+    let known = match &data.baseline {
+        Some(baseline) if !baseline.update => Some(baseline::Baseline::load(&baseline.file)?),
+        _ => None,
+    };
+
+    // tidy files that have been placed in a tidy root are removed once the guards are dropped
+    let mut placed = vec![];
+    let mut units = Vec::with_capacity(data.jsons.len());
+    for (index, json) in data.jsons.iter().enumerate() {
+        if data.jsons.len() > 1 {
+            if index > 0 {
+                log::info!(" ");
+            }
+            log::info!(
+                "Preparing configuration {} ({}/{})",
+                console::style(&json.name).bold(),
+                index + 1,
+                data.jsons.len()
+            );
+            step = LogStep::new();
+        }
+        units.push(prepare(&data, json, &mut step, &mut placed)?);
+    }
+
+    let items: Vec<_> = units
+        .iter()
+        .enumerate()
+        .flat_map(|(index, unit)| unit.paths.iter().map(move |path| (index, path.clone())))
+        .collect();
+    let cached = AtomicUsize::new(0);
+    let export = match (&data.export_fixes, data.fix) {
+        (None, false) => None,
+        (dir, _) => Some(fixes::Export::new(dir.as_deref())?),
+    };
 
     setup_jobs(data.jobs)?;
     log::info!("{} Executing clang-tidy ...\n", step.next(),);
 
-    let pb = indicatif::ProgressBar::new(paths.len() as u64);
+    let pb = indicatif::ProgressBar::new(items.len() as u64);
     pb.set_style(
         indicatif::ProgressStyle::with_template(if console::Term::stdout().size().1 > 80 {
             "{prefix:>12.cyan.bold} [{bar:26}] {pos}/{len} {wide_msg}"
@@ -260,58 +751,192 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
     if log_pretty() {
         pb.set_prefix("Running");
     }
-    let paths: Vec<_> = paths.collect();
 
-    let (failures, warnings) = {
-        let dump: Vec<_> = paths
-            .into_par_iter()
-            .map(|path| {
-                let result = cmd.run_tidy(&path, &build_root, data.fix, data.ignore_warn);
-                let strip_path = match &strip_root {
-                    None => path.clone(),
-                    Some(strip) => {
-                        if let Ok(path) = path.strip_prefix(strip) {
-                            path.to_path_buf()
-                        } else {
-                            path.clone()
+    let count = items.len();
+    let failed = AtomicUsize::new(0);
+    let records: Vec<_> = items
+        .into_par_iter()
+        .enumerate()
+        .map_init(includes::Scanner::new, |scanner, (item, (index, path))| {
+            // no further files are analyzed once the maximum number of failures is reached, files
+            // that are already being analyzed are finished
+            if let Some(max) = data.max_failures {
+                if failed.load(Ordering::Relaxed) >= max {
+                    return None;
+                }
+            }
+            let start = std::time::Instant::now();
+            let unit = &units[index];
+            let profile = unit.profiles.get(&path);
+            let export_file = export.as_ref().map(|export| export.file(item));
+            let run_tidy = || {
+                unit.cmd.run_tidy(
+                    &path,
+                    &unit.build_root,
+                    profile.config_file.as_deref(),
+                    &profile.args,
+                    export_file.as_deref(),
+                    data.ignore_warn,
+                )
+            };
+            let result = match &unit.cache {
+                None => run_tidy(),
+                Some(cache) => {
+                    let database = unit.database.as_ref().ok();
+                    let key = cache.key(&path, &profile.fingerprint, database, scanner);
+                    match cache.get(&key) {
+                        Some(result) => {
+                            log::debug!("Using cached result for {}", path.to_string_lossy());
+                            cached.fetch_add(1, Ordering::Relaxed);
+                            result
+                        }
+                        None => {
+                            let result = run_tidy();
+                            cache.put(&key, &result);
+                            result
                         }
                     }
-                };
-
-                // step log output
-                let (prefix, style) = match result {
-                    cmd::RunResult::Ok => ("Ok", console::Style::new().green().bold()),
-                    cmd::RunResult::Err(_) => ("Error", console::Style::new().red().bold()),
-                    cmd::RunResult::Warn(_) => {
-                        ("Warning", console::Style::new().color256(58).bold())
+                }
+            };
+            let duration = start.elapsed();
+
+            // step log output
+            let (prefix, style) = match result {
+                cmd::RunResult::Ok => ("Ok", console::Style::new().green().bold()),
+                cmd::RunResult::Err(_) => ("Error", console::Style::new().red().bold()),
+                cmd::RunResult::Warn(_) => ("Warning", console::Style::new().color256(58).bold()),
+                cmd::RunResult::Timeout(_) => ("Timeout", console::Style::new().red().bold()),
+            };
+            log_step(prefix, path.as_path(), &unit.strip_root, &pb, style);
+
+            match &result {
+                cmd::RunResult::Ok => (),
+                cmd::RunResult::Err(output) | cmd::RunResult::Timeout(output) => {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    if !log_pretty() && !data.quiet {
+                        log::error!("{}", output.msg);
+                    }
+                }
+                cmd::RunResult::Warn(output) => {
+                    if !log_pretty() {
+                        log::warn!("{}", output.msg);
                     }
-                };
-                log_step(prefix, path.as_path(), &strip_root, &pb, style);
+                }
+            }
+
+            Some(report::Record {
+                path,
+                config: index,
+                duration,
+                result,
+            })
+        })
+        .flatten()
+        .collect();
+
+    let skipped = count - records.len();
+    if skipped > 0 {
+        log::warn!(
+            "Stopped after {} failed files, {} files have not been analyzed",
+            failed.load(Ordering::Relaxed),
+            skipped
+        );
+    }
 
-                // collection
-                match result {
+    if units.iter().any(|unit| unit.cache.is_some()) {
+        log::info!(
+            "Replayed {} of {} results from the cache",
+            console::style(cached.load(Ordering::Relaxed)).bold(),
+            records.len()
+        );
+    }
+
+    match &export {
+        Some(export) if data.dry_run => {
+            let base = std::env::current_dir()
+                .and_then(|dir| dir.canonicalize())
+                .unwrap_or_default();
+            let (diff, applied) = export.diff(count, &base)?;
+            match &data.patch_file {
+                None => print!("{diff}"),
+                Some(file) => fs::write(file, &diff)
+                    .wrap_err(format!("Failed to write '{}'", file.to_string_lossy()))
+                    .suggestion("Please check the path and the permissions of the --patch-file")?,
+            }
+            log::info!(
+                "Found {} fixes for {} files, no file has been modified{}",
+                console::style(applied.fixes).bold(),
+                console::style(applied.files.len()).bold(),
+                match &data.patch_file {
+                    None => "".to_string(),
+                    Some(file) => format!(" (see {})", file.to_string_lossy()),
+                }
+            );
+            log_fixed_files(&applied.files);
+            log_conflicts(&applied.conflicts);
+        }
+        Some(export) if data.fix => {
+            let applied = export.apply(count)?;
+            log::info!(
+                "Applied {} fixes to {} files",
+                console::style(applied.fixes).bold(),
+                console::style(applied.files.len()).bold()
+            );
+            log_fixed_files(&applied.files);
+            log_conflicts(&applied.conflicts);
+        }
+        Some(export) => {
+            let (file, diagnostics) = export.write(count)?;
+            log::info!(
+                "Exported the fixes of {} diagnostics to {}",
+                console::style(diagnostics).bold(),
+                console::style(file.to_string_lossy()).bold()
+            );
+        }
+        None => (),
+    }
+
+    let records = match &data.baseline {
+        None => records,
+        Some(baseline) => {
+            // findings are relative to the root of their configuration, such that a baseline
+            // also matches if the configuration is analyzed together with other configurations
+            let roots: Vec<_> = units.iter().map(|unit| unit.json.root.as_path()).collect();
+            apply_baseline(baseline, known, records, &roots)?
+        }
+    };
+
+    let timeouts = records
+        .iter()
+        .filter(|record| matches!(record.result, cmd::RunResult::Timeout(_)))
+        .count();
+    if timeouts > 0 {
+        log::warn!(
+            "Terminated clang-tidy for {} files exceeding the timeout",
+            console::style(timeouts).bold()
+        );
+    }
+
+    let (failures, warnings) = {
+        let dump: Vec<_> = records
+            .iter()
+            .filter_map(|record| {
+                let strip_root = &units[record.config].strip_root;
+                let path = strip_path(&record.path, strip_root).to_path_buf();
+                match &record.result {
                     cmd::RunResult::Ok => None,
-                    cmd::RunResult::Err(msg) => {
-                        if !log_pretty() && !data.quiet {
-                            log::error!("{}", msg);
-                        }
+                    cmd::RunResult::Err(output) | cmd::RunResult::Timeout(output) => {
                         Some(Dump::Error {
-                            msg,
-                            path: strip_path,
-                        })
-                    }
-                    cmd::RunResult::Warn(msg) => {
-                        if !log_pretty() {
-                            log::warn!("{}", msg);
-                        }
-                        Some(Dump::Warning {
-                            msg,
-                            path: strip_path,
+                            output: output.clone(),
+                            path,
                         })
                     }
+                    cmd::RunResult::Warn(output) => Some(Dump::Warning {
+                        output: output.clone(),
+                        path,
+                    }),
                 }
             })
-            .flatten()
             .collect();
 
         let mut failures = Vec::with_capacity(dump.len());
@@ -319,15 +944,15 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
 
         dump.into_iter().for_each(|item| {
             match item {
-                Dump::Error { msg, path } => failures.push((path, msg)),
-                Dump::Warning { msg, path } => warnings.push((path, msg)),
+                Dump::Error { output, path } => failures.push((path, output)),
+                Dump::Warning { output, path } => warnings.push((path, output)),
             };
         });
         (failures, warnings)
     };
 
     let duration = start.elapsed();
-    if log_pretty() {
+    if log_pretty() && data.output == cli::OutputFormat::Human {
         pb.finish();
 
         println!(
@@ -339,14 +964,56 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
         log::info!("{} Finished in {:#?}", step.next(), duration);
     }
 
-    fn collect_dump(items: Vec<(path::PathBuf, String)>, style: console::Style) -> String {
+    let configs: Vec<_> = units
+        .iter()
+        .map(|unit| report::Config {
+            name: &unit.json.name,
+            root: &unit.json.root,
+            version: unit.cmd.get_version(),
+            command: &unit.cmd_path,
+            tidy_file: unit.tidy_info.as_ref().map(|(file, _)| file.as_path()),
+            tidy_root: unit
+                .tidy_info
+                .as_ref()
+                .and_then(|(_, root)| root.as_deref()),
+            build_root: &unit.build_root,
+            filtered: &unit.filtered,
+        })
+        .collect();
+    let context = report::Context {
+        root: &data.root,
+        name: &data.name,
+        duration,
+        configs: &configs,
+    };
+
+    if data.output == cli::OutputFormat::Json {
+        println!(
+            "{}",
+            report::render(cli::ReportFormat::Json, &records, &context, false)
+        );
+    }
+
+    if let Some(report) = &data.report {
+        report::write(report, &records, &context)?;
+        log::info!(
+            "Report written to {}",
+            console::style(report.file.to_string_lossy()).bold()
+        );
+    }
+
+    fn collect_dump(items: Vec<(path::PathBuf, cmd::Output)>, style: console::Style) -> String {
         items
             .into_iter()
-            .map(|result| {
+            .map(|(path, output)| {
                 format!(
-                    "{}\n{}",
-                    style.apply_to(result.0.to_string_lossy()),
-                    result.1,
+                    "{}{}\n{}",
+                    style.apply_to(path.to_string_lossy()),
+                    match output.summary() {
+                        summary if summary.is_empty() => summary,
+                        summary => format!(" ({summary})"),
+                    },
+                    output.msg,
                 )
             })
             .collect::<Vec<_>>()
@@ -369,29 +1036,32 @@ pub fn run(data: cli::Data) -> eyre::Result<()> {
             "Execution failed for the following files:\n{}\n ",
             collect_dump(failures, console::Style::new().white().bold().on_red()).trim_end()
         )))
+    } else if skipped > 0 {
+        // the failures that stopped the execution have been suppressed, e.g., by the baseline
+        Err(eyre!(
+            "Execution stopped, {skipped} files have not been analyzed"
+        ))
     } else {
         Ok(())
     }
 }
 
+fn strip_path<'a>(path: &'a path::Path, strip_root: &Option<path::PathBuf>) -> &'a path::Path {
+    match strip_root {
+        None => path,
+        Some(strip) => path.strip_prefix(strip).unwrap_or(path),
+    }
+}
+
 fn log_step(
     prefix: &str,
     path: &path::Path,
-    strip_path: &Option<path::PathBuf>,
+    strip_root: &Option<path::PathBuf>,
     progress: &indicatif::ProgressBar,
     style: console::Style,
 ) {
     // let style = console::Style::new().green().bold();
-    let print_path = match strip_path {
-        None => path,
-        Some(strip) => {
-            if let Ok(path) = path.strip_prefix(strip) {
-                path
-            } else {
-                path
-            }
-        }
-    };
+    let print_path = strip_path(path, strip_root);
 
     if log_pretty() {
         progress.println(format!(
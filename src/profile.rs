@@ -0,0 +1,107 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, path,
+};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli, cmd, globs, resolve};
+
+/// Options used for executing `clang-tidy` for a file.
+pub struct Profile {
+    /// Tidy file passed to `clang-tidy` using `--config-file`.
+    pub config_file: Option<path::PathBuf>,
+    /// Additional arguments for `clang-tidy`.
+    pub args: Vec<String>,
+    /// Everything that affects the outcome of `clang-tidy`, used for caching.
+    pub fingerprint: Vec<String>,
+}
+
+impl Profile {
+    fn new(config_file: Option<path::PathBuf>, args: Vec<String>) -> Profile {
+        let mut fingerprint = args.clone();
+        // a tidy file passed via --config-file is not found in the parent directories of the files
+        if let Some(config_file) = &config_file {
+            fingerprint.push(fs::read_to_string(config_file).unwrap_or_default());
+        }
+        Profile {
+            config_file,
+            args,
+            fingerprint,
+        }
+    }
+}
+
+/// Profiles of the configuration file and all groups of its field "paths".
+pub struct Profiles {
+    /// The default profile (index 0), followed by the profiles of all groups.
+    profiles: Vec<Profile>,
+    /// Index of the profile for each file, files without entry use the default profile.
+    assigned: HashMap<path::PathBuf, usize>,
+}
+
+impl Profiles {
+    /// Creates the profiles for the configuration `json` and assigns the profile of the last
+    /// matching group to each of the canonical `paths`.
+    pub fn new(
+        data: &cli::Data,
+        json: &cli::JsonModel,
+        config_file: Option<path::PathBuf>,
+        cmd: &cmd::Runner,
+        paths: &[path::PathBuf],
+    ) -> eyre::Result<Profiles> {
+        let mut profiles = vec![Profile::new(
+            config_file.clone(),
+            resolve::tidy_args(data, json, None),
+        )];
+        let mut assigned = HashMap::new();
+        let known: HashSet<_> = paths.iter().collect();
+
+        for group in json.groups() {
+            let group_file = resolve::group_tidy_file(data, json, group)?;
+            if group_file.is_some() {
+                cmd.supports_config_file()
+                    .wrap_err("Tidy files of groups in 'paths' are passed using --config-file")
+                    .suggestion("Please use clang-tidy version 12.0.0 or higher")?;
+            }
+
+            let index = profiles.len();
+            profiles.push(Profile::new(
+                group_file.or(config_file.clone()),
+                resolve::tidy_args(data, json, Some(group)),
+            ));
+
+            // the glob sets are consumed when matching, they are therefore created for each group
+            let candidates =
+                globs::build_matchers_from(&group.paths, &json.root, "paths", &json.name)?;
+            let filter_pre = globs::build_glob_set_from(&json.filter_pre, "preFilter", &json.name)?;
+            let filter_post =
+                globs::build_glob_set_from(&json.filter_post, "postFilter", &json.name)?;
+
+            let (matched, _) = globs::match_paths(candidates, filter_pre, filter_post);
+            for path in matched.into_iter().filter_map(|p| p.canonicalize().ok()) {
+                // paths that have been removed, e.g., since they did not change, are skipped
+                if known.contains(&path) {
+                    assigned.insert(path, index);
+                }
+            }
+        }
+
+        for (index, profile) in profiles.iter().enumerate() {
+            if !profile.args.is_empty() {
+                log::debug!(
+                    "Additional arguments for clang-tidy (profile {index}): {:?}",
+                    profile.args
+                );
+            }
+        }
+        Ok(Profiles { profiles, assigned })
+    }
+
+    /// Provides the profile for the canonical `path`.
+    pub fn get(&self, path: &path::Path) -> &Profile {
+        let index = self.assigned.get(path).copied().unwrap_or(0);
+        &self.profiles[index]
+    }
+}
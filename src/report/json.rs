@@ -0,0 +1,142 @@
+use std::path;
+
+use serde::Serialize;
+
+use super::{Context, Record};
+use crate::cmd;
+
+/// Version of the JSON document, incremented for incompatible changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Command {
+    path: String,
+    version: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileResult<'a> {
+    path: String,
+    /// One of "ok", "warning", "error" or "timeout".
+    outcome: &'static str,
+    /// Duration in seconds.
+    duration: f64,
+    message: Option<&'a str>,
+    diagnostics: &'a [cmd::Diagnostic],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Totals {
+    files: usize,
+    ok: usize,
+    warnings: usize,
+    errors: usize,
+    timeouts: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Config<'a> {
+    config: &'a str,
+    root: String,
+    tidy_file: Option<String>,
+    tidy_root: Option<String>,
+    build_root: String,
+    command: Command,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary<'a> {
+    format_version: u32,
+    config: &'a str,
+    root: String,
+    /// The following fields are only provided if they are the same for all configurations.
+    tidy_file: Option<String>,
+    tidy_root: Option<String>,
+    build_root: Option<String>,
+    command: Option<Command>,
+    configs: Vec<Config<'a>>,
+    paths: Vec<String>,
+    filtered: Vec<String>,
+    /// Duration in seconds.
+    duration: f64,
+    results: Vec<FileResult<'a>>,
+    totals: Totals,
+}
+
+fn lossy(path: &path::Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+pub fn render(records: &[Record], context: &Context<'_>) -> String {
+    let results: Vec<_> = records
+        .iter()
+        .map(|record| {
+            let (outcome, output) = match &record.result {
+                cmd::RunResult::Ok => ("ok", None),
+                cmd::RunResult::Warn(output) => ("warning", Some(output)),
+                cmd::RunResult::Err(output) => ("error", Some(output)),
+                cmd::RunResult::Timeout(output) => ("timeout", Some(output)),
+            };
+            FileResult {
+                path: lossy(&record.path),
+                outcome,
+                duration: record.duration.as_secs_f64(),
+                message: output.map(|output| output.msg.as_str()),
+                diagnostics: output.map_or(&[], |output| output.diagnostics.as_slice()),
+            }
+        })
+        .collect();
+
+    let count = |outcome: &str| results.iter().filter(|r| r.outcome == outcome).count();
+    let totals = Totals {
+        files: results.len(),
+        ok: count("ok"),
+        warnings: count("warning"),
+        errors: count("error"),
+        timeouts: count("timeout"),
+    };
+
+    let command = |config: &super::Config<'_>| Command {
+        path: lossy(config.command),
+        version: config.version.clone(),
+    };
+    let configs = context
+        .configs
+        .iter()
+        .map(|config| Config {
+            config: config.name,
+            root: lossy(config.root),
+            tidy_file: config.tidy_file.map(lossy),
+            tidy_root: config.tidy_root.map(lossy),
+            build_root: lossy(config.build_root),
+            command: command(config),
+        })
+        .collect();
+
+    let summary = Summary {
+        format_version: FORMAT_VERSION,
+        config: context.name,
+        root: lossy(context.root),
+        tidy_file: context.shared(|c| c.tidy_file.map(lossy)).flatten(),
+        tidy_root: context.shared(|c| c.tidy_root.map(lossy)).flatten(),
+        build_root: context.shared(|c| lossy(c.build_root)),
+        command: context.shared(command),
+        configs,
+        paths: records.iter().map(|record| lossy(&record.path)).collect(),
+        filtered: context
+            .configs
+            .iter()
+            .flat_map(|config| config.filtered.iter().map(|p| lossy(p)))
+            .collect(),
+        duration: context.duration.as_secs_f64(),
+        results,
+        totals,
+    };
+
+    serde_json::to_string_pretty(&summary).unwrap()
+}
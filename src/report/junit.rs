@@ -0,0 +1,119 @@
+use std::fmt::Write;
+
+use super::{Context, Record};
+use crate::cmd;
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c => escaped.push(c),
+            };
+            escaped
+        })
+}
+
+fn message(output: &cmd::Output, default: &str) -> String {
+    match output.summary() {
+        summary if summary.is_empty() => default.to_string(),
+        summary => summary,
+    }
+}
+
+pub fn render(records: &[Record], context: &Context<'_>, warnings_as_failures: bool) -> String {
+    let mut failures = 0usize;
+    let mut skipped = 0usize;
+    let mut cases = String::new();
+
+    for record in records.iter() {
+        let name = match crate::cli::utils::relative_to(&record.path, context.root) {
+            Some(path) => path.to_string_lossy().replace('\\', "/"),
+            None => record.path.to_string_lossy().to_string(),
+        };
+
+        let _ = write!(
+            cases,
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&name),
+            escape(context.config_name(record)),
+            record.duration.as_secs_f64()
+        );
+
+        match &record.result {
+            cmd::RunResult::Ok => cases.push_str(" />\n"),
+            cmd::RunResult::Err(output) => {
+                failures += 1;
+                let _ = write!(
+                    cases,
+                    ">\n      <failure message=\"{}\" type=\"error\">{}</failure>\n    </testcase>\n",
+                    escape(&message(output, "clang-tidy failed")),
+                    escape(&output.msg)
+                );
+            }
+            cmd::RunResult::Timeout(output) => {
+                failures += 1;
+                let _ = write!(
+                    cases,
+                    ">\n      <failure message=\"{}\" type=\"timeout\">{}</failure>\n    </testcase>\n",
+                    escape("clang-tidy timed out"),
+                    escape(&output.msg)
+                );
+            }
+            cmd::RunResult::Warn(output) if warnings_as_failures => {
+                failures += 1;
+                let _ = write!(
+                    cases,
+                    ">\n      <failure message=\"{}\" type=\"warning\">{}</failure>\n    </testcase>\n",
+                    escape(&message(output, "warnings encountered")),
+                    escape(&output.msg)
+                );
+            }
+            cmd::RunResult::Warn(output) => {
+                skipped += 1;
+                let _ = write!(
+                    cases,
+                    ">\n      <skipped message=\"{}\" />\n      <system-out>{}</system-out>\n    </testcase>\n",
+                    escape(&message(output, "warnings encountered")),
+                    escape(&output.msg)
+                );
+            }
+        }
+    }
+
+    let attributes = format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"",
+        records.len(),
+        failures,
+        skipped,
+        context.duration.as_secs_f64()
+    );
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"run-clang-tidy\" {attributes}>\n  \
+         <testsuite name=\"{}\" {attributes}>\n\
+         {cases}  \
+         </testsuite>\n\
+         </testsuites>\n",
+        escape(context.name)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            "a &lt;b&gt; &amp; &quot;c&quot;\n",
+            escape("a <b> & \"c\"\x1b\n")
+        );
+    }
+}
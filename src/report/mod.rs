@@ -0,0 +1,143 @@
+use std::{fs, path, time};
+
+#[allow(unused_imports)]
+use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
+
+use crate::{cli, cmd};
+
+mod json;
+mod junit;
+mod sarif;
+
+/// Outcome of the execution of `clang-tidy` for a single file.
+pub struct Record {
+    /// Canonical path of the analyzed file.
+    pub path: path::PathBuf,
+    /// Index of the configuration the file has been analyzed with, see `Context::configs`.
+    pub config: usize,
+    pub duration: time::Duration,
+    pub result: cmd::RunResult,
+}
+
+/// Information about the run that is common to all records.
+pub struct Context<'a> {
+    /// Common parent directory of all configuration files, used to create relative paths.
+    pub root: &'a path::Path,
+    /// Name of the configuration or workspace file(s).
+    pub name: &'a str,
+    /// Total duration of the run.
+    pub duration: time::Duration,
+    /// Configurations that have been analyzed within the run.
+    pub configs: &'a [Config<'a>],
+}
+
+/// Information about a single configuration file of the run.
+pub struct Config<'a> {
+    /// Name of the configuration file.
+    pub name: &'a str,
+    /// Parent directory of the configuration file.
+    pub root: &'a path::Path,
+    /// Version of `clang-tidy` that has been used for the analysis.
+    pub version: Option<String>,
+    /// Path to the `clang-tidy` command.
+    pub command: &'a path::Path,
+    /// Resolved tidy file and root, if any.
+    pub tidy_file: Option<&'a path::Path>,
+    pub tidy_root: Option<&'a path::Path>,
+    pub build_root: &'a path::Path,
+    /// Paths that matched the globs but have been removed by the post-filter.
+    pub filtered: &'a [path::PathBuf],
+}
+
+impl Context<'_> {
+    /// Provides the value of a field that is common to all configurations, if any.
+    fn shared<T: PartialEq>(&self, field: impl Fn(&Config<'_>) -> T) -> Option<T> {
+        let mut values = self.configs.iter().map(field);
+        let first = values.next()?;
+        values.all(|value| value == first).then_some(first)
+    }
+
+    /// Name of the configuration of a `record`.
+    fn config_name(&self, record: &Record) -> &str {
+        self.configs
+            .get(record.config)
+            .map_or(self.name, |config| config.name)
+    }
+}
+
+/// Provides the URI of a path, relative to the `root` of the context if possible.
+///
+/// Returns the URI and whether or not it is relative to the root.
+fn uri_for(path: &path::Path, root: &path::Path) -> (String, bool) {
+    match cli::utils::relative_to(path, root) {
+        Some(relative) => (encode_uri(&relative.to_string_lossy()), true),
+        None => (file_uri(path), false),
+    }
+}
+
+fn file_uri(path: &path::Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    // canonical paths on Windows use the verbatim prefix \\?\
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+
+    if path.starts_with('/') {
+        format!("file://{}", encode_uri(path))
+    } else {
+        format!("file:///{}", encode_uri(path))
+    }
+}
+
+fn encode_uri(path: &str) -> String {
+    path.replace('\\', "/")
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+pub fn render(
+    format: cli::ReportFormat,
+    records: &[Record],
+    context: &Context<'_>,
+    warnings_as_failures: bool,
+) -> String {
+    match format {
+        cli::ReportFormat::Sarif => sarif::render(records, context),
+        cli::ReportFormat::Junit => junit::render(records, context, warnings_as_failures),
+        cli::ReportFormat::Json => json::render(records, context),
+    }
+}
+
+pub fn write(report: &cli::Report, records: &[Record], context: &Context<'_>) -> eyre::Result<()> {
+    let content = render(report.format, records, context, report.warnings_as_failures);
+
+    fs::write(&report.file, content)
+        .wrap_err(format!(
+            "Failed to write report to '{}'",
+            report.file.to_string_lossy()
+        ))
+        .suggestion("Please make sure that the parent directory of the report file exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_uri() {
+        let root = path::Path::new("/prj/cfg");
+        assert_eq!(
+            ("../src/my%20file.c".to_string(), true),
+            uri_for(path::Path::new("/prj/src/my file.c"), root)
+        );
+        assert_eq!(
+            "file:///prj/src/main.c",
+            file_uri(path::Path::new("/prj/src/main.c"))
+        );
+    }
+}
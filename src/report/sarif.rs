@@ -0,0 +1,211 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde_json::{json, Value};
+
+use super::{file_uri, uri_for, Context, Record};
+use crate::cmd;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const ROOT_ID: &str = "SRCROOT";
+
+fn level(severity: cmd::Severity) -> &'static str {
+    match severity {
+        cmd::Severity::Error => "error",
+        cmd::Severity::Warning => "warning",
+        cmd::Severity::Remark | cmd::Severity::Note => "note",
+    }
+}
+
+fn help_uri(check: &str) -> Option<String> {
+    // compiler diagnostics are not documented as clang-tidy checks
+    if check.starts_with("clang-diagnostic-") {
+        return None;
+    }
+    let (group, name) = match check.strip_prefix("clang-analyzer-") {
+        Some(name) => ("clang-analyzer", name),
+        None => check.split_once('-')?,
+    };
+    Some(format!(
+        "https://clang.llvm.org/extra/clang-tidy/checks/{group}/{name}.html"
+    ))
+}
+
+fn location(loc: &cmd::Location, context: &Context<'_>) -> Value {
+    let (uri, relative) = uri_for(&loc.file, context.root);
+    let mut artifact = json!({ "uri": uri });
+    if relative {
+        artifact["uriBaseId"] = json!(ROOT_ID);
+    }
+
+    json!({
+        "physicalLocation": {
+            "artifactLocation": artifact,
+            "region": {
+                "startLine": loc.line,
+                "startColumn": loc.column,
+            }
+        }
+    })
+}
+
+fn result(diagnostic: &cmd::Diagnostic, rules: &[&str], context: &Context<'_>) -> Value {
+    let mut result = json!({
+        "level": level(diagnostic.severity),
+        "message": { "text": diagnostic.message },
+    });
+
+    if let Some(check) = &diagnostic.check {
+        result["ruleId"] = json!(check);
+        if let Some(index) = rules.iter().position(|rule| rule == check) {
+            result["ruleIndex"] = json!(index);
+        }
+    }
+    if let Some(loc) = &diagnostic.location {
+        result["locations"] = json!([location(loc, context)]);
+    }
+
+    let related: Vec<_> = diagnostic
+        .notes
+        .iter()
+        .filter_map(|note| {
+            note.location.as_ref().map(|loc| {
+                let mut related = location(loc, context);
+                related["message"] = json!({ "text": note.message });
+                related
+            })
+        })
+        .collect();
+    if !related.is_empty() {
+        result["relatedLocations"] = json!(related);
+    }
+    result
+}
+
+pub fn render(records: &[Record], context: &Context<'_>) -> String {
+    // diagnostics in headers are reported for every translation unit that includes the header,
+    // such duplicates are only reported once.
+    let mut seen = HashSet::new();
+    let diagnostics: Vec<_> = records
+        .iter()
+        .filter_map(|record| match &record.result {
+            cmd::RunResult::Err(output)
+            | cmd::RunResult::Warn(output)
+            | cmd::RunResult::Timeout(output) => Some(output.diagnostics.iter()),
+            cmd::RunResult::Ok => None,
+        })
+        .flatten()
+        .filter(|d| {
+            seen.insert((
+                d.location.clone(),
+                d.severity,
+                d.message.clone(),
+                d.check.clone(),
+            ))
+        })
+        .collect();
+
+    let rules: Vec<_> = diagnostics
+        .iter()
+        .filter_map(|d| d.check.as_deref())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let rules_json: Vec<_> = rules
+        .iter()
+        .map(|rule| {
+            let mut json = json!({ "id": rule });
+            if let Some(uri) = help_uri(rule) {
+                json["helpUri"] = json!(uri);
+            }
+            json
+        })
+        .collect();
+
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|d| result(d, &rules, context))
+        .collect();
+
+    // failures without any diagnostic, e.g., if clang-tidy crashed, and timeouts are reported as
+    // notifications of the tool execution
+    let notifications: Vec<_> = records
+        .iter()
+        .filter_map(|record| match &record.result {
+            cmd::RunResult::Err(output) if output.diagnostics.is_empty() => Some((record, output)),
+            cmd::RunResult::Timeout(output) => Some((record, output)),
+            _ => None,
+        })
+        .map(|(record, output)| {
+            json!({
+                "level": "error",
+                "message": { "text": output.msg },
+                "locations": [location(
+                    &cmd::Location {
+                        file: record.path.clone(),
+                        line: 1,
+                        column: 1,
+                    },
+                    context
+                )],
+            })
+        })
+        .collect();
+
+    let successful = !records.iter().any(|record| {
+        matches!(
+            record.result,
+            cmd::RunResult::Err(_) | cmd::RunResult::Timeout(_)
+        )
+    });
+
+    let mut root_uri = file_uri(context.root);
+    if !root_uri.ends_with('/') {
+        root_uri.push('/');
+    }
+
+    let mut driver = json!({
+        "name": "clang-tidy",
+        "informationUri": "https://clang.llvm.org/extra/clang-tidy/",
+        "rules": rules_json,
+    });
+    if let Some(version) = context.shared(|config| config.version.clone()).flatten() {
+        driver["version"] = json!(version);
+    }
+
+    let sarif = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": driver },
+            "invocations": [{
+                "executionSuccessful": successful,
+                "toolExecutionNotifications": notifications,
+            }],
+            "originalUriBaseIds": {
+                ROOT_ID: { "uri": root_uri },
+            },
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_uri() {
+        assert_eq!(
+            Some("https://clang.llvm.org/extra/clang-tidy/checks/bugprone/macro-parentheses.html"),
+            help_uri("bugprone-macro-parentheses").as_deref()
+        );
+        assert_eq!(
+            Some("https://clang.llvm.org/extra/clang-tidy/checks/clang-analyzer/core.NullDereference.html"),
+            help_uri("clang-analyzer-core.NullDereference").as_deref()
+        );
+        assert_eq!(None, help_uri("clang-diagnostic-error"));
+    }
+}
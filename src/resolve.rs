@@ -5,11 +5,14 @@ use crate::cli::{self, utils};
 #[allow(unused_imports)]
 use color_eyre::{eyre::eyre, eyre::WrapErr, Help};
 
-fn resolve_tidy_file(data: &cli::Data) -> eyre::Result<eyre::Result<path::PathBuf>> {
-    let tidy_json = match &data.json.tidy_file {
+fn resolve_tidy_file(
+    data: &cli::Data,
+    json: &cli::JsonModel,
+) -> eyre::Result<eyre::Result<path::PathBuf>> {
+    let tidy_json = match &json.tidy_file {
         None => None,
         Some(path) => {
-            let mut full_path = path::PathBuf::from(data.json.root.as_path());
+            let mut full_path = path::PathBuf::from(json.root.as_path());
             full_path.push(path);
             // do not perform the validation for the 'tidyFile' yet since a valid override
             // might have been passed as parameter to the tool
@@ -35,7 +38,7 @@ fn resolve_tidy_file(data: &cli::Data) -> eyre::Result<eyre::Result<path::PathBu
                     .wrap_err("Invalid configuration for 'tidyFile'")
                     .suggestion(format!(
                         "Check the content of the field 'tidyFile' in {}.",
-                        data.json.name
+                        json.name
                     ))?;
                 Ok(path.canonicalize().unwrap())
             }
@@ -46,7 +49,7 @@ fn resolve_tidy_file(data: &cli::Data) -> eyre::Result<eyre::Result<path::PathBu
                         specified in '{}' is overridden by the \
                         command-line parameter: '{}'\n",
                     s_cfg.to_string_lossy(),
-                    data.json.name,
+                    json.name,
                     s_cli.as_path().to_string_lossy()
                 );
                 Ok(path::PathBuf::from(s_cli.as_path()).canonicalize().unwrap())
@@ -57,15 +60,36 @@ fn resolve_tidy_file(data: &cli::Data) -> eyre::Result<eyre::Result<path::PathBu
     Ok(tidy)
 }
 
-pub fn tidy_and_root(data: &cli::Data) -> eyre::Result<Option<(path::PathBuf, path::PathBuf)>> {
-    let tidy_file = resolve_tidy_file(data)?;
-    let tidy_root = match &data.json.tidy_root {
-        None => None,
-        Some(path) => {
+/// Resolves the tidy file and the optional root folder for placing the tidy file.
+///
+/// Without a root folder, the tidy file can only be passed to `clang-tidy` using the option
+/// `--config-file`, which is checked once the version of `clang-tidy` is known.
+pub fn tidy_and_root(
+    data: &cli::Data,
+    json: &cli::JsonModel,
+) -> eyre::Result<Option<(path::PathBuf, Option<path::PathBuf>)>> {
+    let tidy_file = resolve_tidy_file(data, json)?;
+    let tidy_root = match (&json.tidy_root, &data.tidy_root) {
+        (None, None) => None,
+        // tidy root defined as CLI parameter, with or without .json configuration
+        (s_cfg, Some(s_cli)) => {
+            if let Some(s_cfg) = s_cfg {
+                log::debug!(
+                    "Override detected:\ntidy root '{}' \
+                        specified in '{}' is overridden by the \
+                        command-line parameter: '{}'\n",
+                    s_cfg.to_string_lossy(),
+                    json.name,
+                    s_cli.to_string_lossy()
+                );
+            }
+            Some(s_cli.canonicalize().unwrap())
+        }
+        (Some(path), None) => {
             let path = if path.is_absolute() {
                 path::PathBuf::from(path.as_path())
             } else {
-                let mut full_path = path::PathBuf::from(data.json.root.as_path());
+                let mut full_path = path::PathBuf::from(json.root.as_path());
                 full_path.push(path);
                 full_path
             };
@@ -82,10 +106,12 @@ pub fn tidy_and_root(data: &cli::Data) -> eyre::Result<Option<(path::PathBuf, pa
         }
     };
 
-    if let Err(tidy_err) = tidy_file {
-        match tidy_root {
-            // scenario: no root folder and no tidy file specified, simply run clang-format
-            // and assume that there is a .clang-format file in the root folder of all files
+    match tidy_file {
+        // scenario: tidy file has been specified, with or without root folder
+        Ok(tidy_file) => Ok(Some((tidy_file, tidy_root))),
+        Err(tidy_err) => match tidy_root {
+            // scenario: no root folder and no tidy file specified, simply run clang-tidy
+            // and assume that there is a .clang-tidy file in the root folder of all files
             None => Ok(None),
             // unsupported scenario: root specified but missing tidy file
             Some(_) => Err(tidy_err.wrap_err(
@@ -96,28 +122,15 @@ pub fn tidy_and_root(data: &cli::Data) -> eyre::Result<Option<(path::PathBuf, pa
                 "Specify the tidy file using the command line \
                  parameter or the field 'tidyRoot' within the configuration file.",
             ),
-        }
-    } else {
-        match tidy_root {
-            // scenario: root folder and tidy file have been specified. it is necessary to copy
-            // the tidy file to the root folder before executing clang-format
-            Some(tidy_root) => Ok(Some((tidy_file.unwrap(), tidy_root))),
-            // unsupported scenario: tidy file specified but missing root folder
-            None => Err(eyre::eyre!("Missing root folder configuration",)
-                .wrap_err(format!(
-                    "Found tidy file '{}' but could not find root folder configuration",
-                    tidy_file.unwrap().to_string_lossy()
-                ))
-                .suggestion("Please add the field 'tidyRoot' to your configuration file.")),
-        }
+        },
     }
 }
 
-pub fn build_root(data: &cli::Data) -> eyre::Result<path::PathBuf> {
-    let build_root_json = match &data.json.build_root {
+pub fn build_root(data: &cli::Data, json: &cli::JsonModel) -> eyre::Result<path::PathBuf> {
+    let build_root_json = match &json.build_root {
         None => None,
         Some(path) => {
-            let mut full_path = path::PathBuf::from(data.json.root.as_path());
+            let mut full_path = path::PathBuf::from(json.root.as_path());
             full_path.push(path);
             // do not perform the validation for the 'buildRoot' yet since a valid override
             // might have been passed as parameter to the tool
@@ -141,7 +154,7 @@ pub fn build_root(data: &cli::Data) -> eyre::Result<path::PathBuf> {
                     .wrap_err("Invalid configuration for 'buildRoot'")
                     .suggestion(format!(
                         "Check the content of the field 'buildRoot' in {}.",
-                        data.json.name
+                        json.name
                     ))?;
                 Ok(path.canonicalize().unwrap())
             }
@@ -152,7 +165,7 @@ pub fn build_root(data: &cli::Data) -> eyre::Result<path::PathBuf> {
                         specified in '{}' is overridden by the \
                         command-line parameter: '{}'\n",
                     s_cfg.to_string_lossy(),
-                    data.json.name,
+                    json.name,
                     s_cli.as_path().to_string_lossy()
                 );
                 Ok(path::PathBuf::from(s_cli.as_path()).canonicalize().unwrap())
@@ -163,10 +176,10 @@ pub fn build_root(data: &cli::Data) -> eyre::Result<path::PathBuf> {
     build_root
 }
 
-pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
+pub fn command(data: &cli::Data, json: &cli::JsonModel) -> eyre::Result<path::PathBuf> {
     let mut from_json = false;
 
-    let cmd = match &data.json.command {
+    let cmd = match &json.command {
         None => match &data.command {
             // use default value if not specified in configuration file nor as parameter
             None => path::PathBuf::from("clang-tidy"),
@@ -185,7 +198,7 @@ pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
                     "Override detected:\nCommand '{}' \
                         specified in '{}' is overridden by the command-line parameter: '{}'\n",
                     cmd_cfg.to_string_lossy(),
-                    data.json.name,
+                    json.name,
                     cmd_cli.as_path().to_string_lossy()
                 );
                 path::PathBuf::from(cmd_cli.as_path())
@@ -194,7 +207,7 @@ pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
     };
 
     if from_json {
-        return utils::executable_or_exists(cmd.as_path(), Some(data.json.root.as_path()))
+        return utils::executable_or_exists(cmd.as_path(), Some(json.root.as_path()))
             .wrap_err("Invalid configuration for field 'command'")
             .suggestion(
                 "When using relative paths for the field 'command' please \
@@ -205,10 +218,455 @@ pub fn command(data: &cli::Data) -> eyre::Result<path::PathBuf> {
     Ok(cmd)
 }
 
+/// Replaces the `value` of the `field` in the configuration `json` by the command-line
+/// parameter `cli`, if any.
+fn override_field<T>(
+    json: &cli::JsonModel,
+    field: &str,
+    value: Option<T>,
+    cli: &Option<T>,
+) -> Option<T>
+where
+    T: Clone + std::fmt::Debug,
+{
+    match (value, cli) {
+        (value, None) => value,
+        (None, Some(s_cli)) => Some(s_cli.clone()),
+        (Some(s_cfg), Some(s_cli)) => {
+            log::debug!(
+                "Override detected:\n'{field}' {:?} \
+                    specified in '{}' is overridden by the \
+                    command-line parameter: {:?}\n",
+                s_cfg,
+                json.name,
+                s_cli
+            );
+            Some(s_cli.clone())
+        }
+    }
+}
+
+/// Applies the command-line parameters that override fields of the configuration `json`, and
+/// validates the resulting configuration. The tidy file, tidy root, build root and command are
+/// resolved separately, see `tidy_and_root`, `build_root` and `command`.
+pub fn config(data: &cli::Data, json: cli::JsonModel) -> eyre::Result<cli::JsonModel> {
+    let mut json = json;
+
+    // paths passed as parameter are relative to the current working directory, whereas the
+    // globs of the configuration are resolved relative to its root
+    let paths = match &data.paths {
+        None => None,
+        Some(paths) => {
+            let cwd = std::env::current_dir()
+                .wrap_err("Failed to determine the current working directory")?
+                .canonicalize()
+                .unwrap();
+            let prefix = utils::glob_prefix(&cwd, &json.root).ok_or(eyre!(
+                "The current working directory and '{}' do not share a common root",
+                json.name
+            ))?;
+            Some(
+                paths
+                    .iter()
+                    .map(|glob| cli::PathEntry::Glob(utils::prefix_glob(&prefix, glob.clone())))
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+    json.paths = override_field(&json, "paths", json.paths.clone(), &paths);
+    json.source = override_field(&json, "source", json.source, &data.source);
+    json.filter_pre = override_field(
+        &json,
+        "filterPre",
+        json.filter_pre.clone(),
+        &data.filter_pre,
+    );
+    json.filter_post = override_field(
+        &json,
+        "filterPost",
+        json.filter_post.clone(),
+        &data.filter_post,
+    );
+    json.extra_args = override_field(
+        &json,
+        "extraArgs",
+        json.extra_args.clone(),
+        &data.extra_args,
+    );
+    json.extra_args_before = override_field(
+        &json,
+        "extraArgsBefore",
+        json.extra_args_before.clone(),
+        &data.extra_args_before,
+    );
+    json.checks = override_field(&json, "checks", json.checks.clone(), &data.checks);
+    json.header_filter = override_field(
+        &json,
+        "headerFilter",
+        json.header_filter.clone(),
+        &data.header_filter,
+    );
+    json.warnings_as_errors = override_field(
+        &json,
+        "warningsAsErrors",
+        json.warnings_as_errors.clone(),
+        &data.warnings_as_errors,
+    );
+    json.timeout = override_field(&json, "timeout", json.timeout, &data.timeout);
+    if json.timeout == Some(0) {
+        return Err(eyre!(
+            "Invalid configuration for 'timeout' in '{}'",
+            json.name
+        ))
+        .suggestion("Please provide a timeout of at least one second");
+    }
+
+    // the inline configuration replaces any tidy file of the configuration
+    if data.tidy_config.is_some() {
+        if let Some(tidy_file) = json.tidy_file.take() {
+            log::debug!(
+                "Override detected:\ntidy file '{}' specified in '{}' is overridden by the \
+                    command-line parameter --tidy-config\n",
+                tidy_file.to_string_lossy(),
+                json.name,
+            );
+        }
+    }
+    json.tidy_config = override_field(
+        &json,
+        "tidyConfig",
+        json.tidy_config.clone(),
+        &data.tidy_config,
+    );
+
+    if json.paths.is_none() && json.source != Some(cli::Source::CompileCommands) {
+        return Err(eyre!("Missing field 'paths' in '{}'", json.name)).suggestion(
+            "Please add the field 'paths' or set the field 'source' to 'compileCommands'",
+        );
+    }
+    Ok(json)
+}
+
+/// Translates the inline configuration into the value of the `--config` option.
+fn tidy_config(config: &cli::TidyConfig) -> String {
+    let mut json = serde_json::Map::new();
+
+    if let Some(checks) = &config.checks {
+        json.insert("Checks".into(), checks.join(",").into());
+    }
+    if let Some(checks) = &config.warnings_as_errors {
+        json.insert("WarningsAsErrors".into(), checks.join(",").into());
+    }
+    if let Some(regex) = &config.header_filter_regex {
+        json.insert("HeaderFilterRegex".into(), regex.clone().into());
+    }
+    if let Some(options) = &config.check_options {
+        // the list format for options is supported by all versions of clang-tidy
+        let options: Vec<_> = options
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                serde_json::json!({ "key": key, "value": value })
+            })
+            .collect();
+        json.insert("CheckOptions".into(), options.into());
+    }
+
+    // JSON is valid YAML and thus accepted by clang-tidy
+    serde_json::Value::Object(json).to_string()
+}
+
+/// Provides the inline tidy configuration as `--config` argument, unless it is overridden by
+/// a tidy file passed as command-line parameter.
+pub fn tidy_config_arg(data: &cli::Data, json: &cli::JsonModel) -> Option<String> {
+    let config = json.tidy_config.as_ref()?;
+
+    if let Some(tidy_file) = &data.tidy_file {
+        log::debug!(
+            "Override detected:\ntidy configuration specified in '{}' is overridden by the \
+                command-line parameter: '{}'\n",
+            json.name,
+            tidy_file.to_string_lossy()
+        );
+        return None;
+    }
+    Some(format!("--config={}", tidy_config(config)))
+}
+
+/// Provides the additional arguments for `clang-tidy`, i.e., the options of the configuration
+/// file followed by the arguments passed on the command line. The options of the `group` replace
+/// or extend the options of the configuration file.
+pub fn tidy_args(
+    data: &cli::Data,
+    json: &cli::JsonModel,
+    group: Option<&cli::PathGroup>,
+) -> Vec<String> {
+    // a tidy file of the group replaces the inline configuration
+    let mut args: Vec<_> = match group.and_then(|group| group.tidy_file.as_ref()) {
+        Some(_) if data.tidy_file.is_none() => vec![],
+        _ => tidy_config_arg(data, json).into_iter().collect(),
+    };
+
+    let extra_args = json.extra_args.iter().flatten().chain(
+        group
+            .and_then(|group| group.extra_args.as_ref())
+            .into_iter()
+            .flatten(),
+    );
+    let lists = json
+        .extra_args_before
+        .iter()
+        .flatten()
+        .map(|value| ("--extra-arg-before", value))
+        .chain(extra_args.map(|value| ("--extra-arg", value)));
+    for (option, value) in lists {
+        args.push(format!("{option}={value}"));
+    }
+
+    let values = [
+        (
+            "--checks",
+            group
+                .and_then(|group| group.checks.as_ref())
+                .or(json.checks.as_ref()),
+        ),
+        ("--header-filter", json.header_filter.as_ref()),
+        (
+            "--warnings-as-errors",
+            group
+                .and_then(|group| group.warnings_as_errors.as_ref())
+                .or(json.warnings_as_errors.as_ref()),
+        ),
+    ];
+    for (option, value) in values {
+        if let Some(value) = value {
+            args.push(format!("{option}={value}"));
+        }
+    }
+
+    args.extend(data.tidy_args.iter().cloned());
+    args
+}
+
+/// Resolves the tidy file of a group of paths, unless it is overridden by a tidy file passed as
+/// command-line parameter.
+pub fn group_tidy_file(
+    data: &cli::Data,
+    json: &cli::JsonModel,
+    group: &cli::PathGroup,
+) -> eyre::Result<Option<path::PathBuf>> {
+    let path = match &group.tidy_file {
+        None => return Ok(None),
+        Some(path) => path,
+    };
+
+    if let Some(s_cli) = &data.tidy_file {
+        log::debug!(
+            "Override detected:\ntidy file '{}' of a group in '{}' is overridden by the \
+                command-line parameter: '{}'\n",
+            path.to_string_lossy(),
+            json.name,
+            s_cli.to_string_lossy()
+        );
+        return Ok(None);
+    }
+
+    let path = utils::file_with_name_or_ext(json.root.join(path), ".clang-tidy")
+        .wrap_err("Invalid configuration for 'tidyFile' of a group in 'paths'")
+        .suggestion(format!(
+            "Check the content of the field 'paths' in {}.",
+            json.name
+        ))?;
+    Ok(Some(path.canonicalize().unwrap()))
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
+    use super::cli;
+
+    /// Parses the command-line `args` for a valid configuration file.
+    fn data(args: &[&str]) -> cli::Data {
+        let json = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test-files/json/test-ok-tidy.json"
+        );
+        let args = ["run-clang-tidy", json]
+            .into_iter()
+            .chain(args.iter().copied());
+        let matches = cli::Builder::app().get_matches_from(args);
+        cli::Builder { matches }.parse().unwrap()
+    }
+
+    /// Provides the configuration `content`, located in the folder "test-files/json".
+    fn config(content: &str) -> cli::JsonModel {
+        let mut json: cli::JsonModel = serde_json::from_str(content).unwrap();
+        json.root = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/test-files/json"));
+        json.name = "test.json".to_string();
+        json
+    }
+
+    #[test]
+    fn test_tidy_config() {
+        let config: super::cli::TidyConfig = serde_json::from_str(
+            r#"{
+                "checks": ["-*", "bugprone-*"],
+                "checkOptions": { "readability-function-size.LineThreshold": 100 },
+                "headerFilterRegex": ".*"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"{"CheckOptions":[{"key":"readability-function-size.LineThreshold","value":"100"}],"Checks":"-*,bugprone-*","HeaderFilterRegex":".*"}"#,
+            super::tidy_config(&config)
+        );
+    }
+
+    #[test]
+    fn test_tidy_args() {
+        let json = config(
+            r#"{
+                "paths": [],
+                "extraArgs": ["-DA", "-DB"],
+                "extraArgsBefore": ["-std=c11"],
+                "checks": "-*,bugprone-*",
+                "headerFilter": ".*",
+                "warningsAsErrors": "*"
+            }"#,
+        );
+
+        // the options of the configuration are followed by the arguments after '--'
+        assert_eq!(
+            vec![
+                "--extra-arg-before=-std=c11",
+                "--extra-arg=-DA",
+                "--extra-arg=-DB",
+                "--checks=-*,bugprone-*",
+                "--header-filter=.*",
+                "--warnings-as-errors=*",
+                "--use-color",
+                "--extra-arg=-DC",
+            ],
+            super::tidy_args(
+                &data(&["--", "--use-color", "--extra-arg=-DC"]),
+                &json,
+                None
+            )
+        );
+
+        // options that are not specified are not passed
+        assert!(super::tidy_args(&data(&[]), &config(r#"{ "paths": [] }"#), None).is_empty());
+    }
+
+    #[test]
+    fn test_tidy_args_group() {
+        let json = config(
+            r#"{
+                "paths": [
+                    "../c-demo/**/*.c",
+                    {
+                        "paths": ["../c-demo/pkg_a/**/*.c"],
+                        "checks": "-*,readability-*",
+                        "warningsAsErrors": "readability-*",
+                        "extraArgs": ["-DGROUP"]
+                    }
+                ],
+                "extraArgs": ["-DTOP"],
+                "checks": "-*,bugprone-*",
+                "warningsAsErrors": "*"
+            }"#,
+        );
+        let data = data(&[]);
+        let group = json.groups()[0];
+
+        assert_eq!(
+            vec![
+                "--extra-arg=-DTOP",
+                "--checks=-*,bugprone-*",
+                "--warnings-as-errors=*",
+            ],
+            super::tidy_args(&data, &json, None)
+        );
+
+        // the checks of the group replace the configured ones, its extra arguments are added
+        assert_eq!(
+            vec![
+                "--extra-arg=-DTOP",
+                "--extra-arg=-DGROUP",
+                "--checks=-*,readability-*",
+                "--warnings-as-errors=readability-*",
+            ],
+            super::tidy_args(&data, &json, Some(group))
+        );
+    }
+
+    #[test]
+    fn test_tidy_args_last_group() {
+        let json = config(
+            r#"{
+                "paths": [
+                    { "paths": ["../c-demo/**/*.c"], "checks": "-*,bugprone-*" },
+                    { "paths": ["../c-demo/pkg_a/**/*.c"], "checks": "-*,readability-*" }
+                ]
+            }"#,
+        );
+        let root = json.root.join("../c-demo").canonicalize().unwrap();
+        let paths = [
+            root.join("pkg_a/module_a/module_a.c"),
+            root.join("pkg_b/module_b/module_b.c"),
+        ];
+        let profiles = crate::profile::Profiles::new(
+            &data(&[]),
+            &json,
+            None,
+            &crate::cmd::Runner::new("clang-tidy"),
+            &paths,
+        )
+        .unwrap();
+
+        // the profile of the last matching group is used
+        assert_eq!(
+            vec!["--checks=-*,readability-*"],
+            profiles.get(&paths[0]).args
+        );
+        assert_eq!(vec!["--checks=-*,bugprone-*"], profiles.get(&paths[1]).args);
+    }
+
+    #[test]
+    fn test_group_tidy_file() {
+        let json = config(
+            r#"{
+                "paths": [{ "paths": ["../c-demo/**/*.c"], "tidyFile": "../clang-tidy/named.clang-tidy" }],
+                "tidyConfig": { "checks": ["-*", "bugprone-*"] }
+            }"#,
+        );
+        let group = json.groups()[0];
+        let named = json.root.join("../clang-tidy/named.clang-tidy");
+
+        // the tidy file of the group replaces the inline configuration
+        let cli = data(&[]);
+        assert_eq!(
+            Some(named.canonicalize().unwrap()),
+            super::group_tidy_file(&cli, &json, group).unwrap()
+        );
+        assert!(super::tidy_args(&cli, &json, Some(group)).is_empty());
+        assert_eq!(
+            vec![r#"--config={"Checks":"-*,bugprone-*"}"#],
+            super::tidy_args(&cli, &json, None)
+        );
+
+        // the tidy file passed on the command line overrides the one of the group
+        let tidy = json.root.join("../clang-tidy/.clang-tidy");
+        let cli = data(&["--tidy", tidy.to_str().unwrap()]);
+        assert_eq!(None, super::group_tidy_file(&cli, &json, group).unwrap());
+        assert!(super::tidy_args(&cli, &json, Some(group)).is_empty());
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn test_command_path() {
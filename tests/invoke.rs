@@ -2,7 +2,13 @@
 // https://github.com/mattgathu/duma/blob/master/tests/
 // https://crates.io/crates/assert_cmd
 
-use std::{fs::File, io::Read, path, thread, time};
+use std::{
+    fs::File,
+    io::Read,
+    path,
+    sync::{Mutex, MutexGuard},
+    thread, time,
+};
 
 use assert_cmd::{cargo, Command};
 use clap::crate_name;
@@ -36,7 +42,7 @@ fn crate_root_rel(path: &str) -> path::PathBuf {
 
 #[test]
 fn invoke_subs() {
-    // an empty command fails since <JSON> is required
+    // an empty command fails since no configuration file can be found
     cmd().assert().failure();
 
     // with and without proper PATH setup
@@ -50,6 +56,11 @@ fn invoke_subs() {
 }
 
 fn run_cmd_and_assert(cmd: &mut Command, should_pass: bool) {
+    run_cmd_and_capture(cmd, should_pass);
+}
+
+/// Runs the command and provides its output, stdout followed by stderr.
+fn run_cmd_and_capture(cmd: &mut Command, should_pass: bool) -> String {
     println!("command {:?} should pass {}", cmd, should_pass);
     let output = cmd.output().unwrap();
 
@@ -60,8 +71,12 @@ fn run_cmd_and_assert(cmd: &mut Command, should_pass: bool) {
         should_pass
     );
     println!("status: {}", output.status);
-    println!("{}", String::from_utf8(output.stdout).unwrap());
-    println!("{}", String::from_utf8(output.stderr).unwrap());
+    let captured = format!(
+        "{}\n{}",
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap()
+    );
+    println!("{captured}");
     // }
 
     if cfg!(windows) {
@@ -70,6 +85,47 @@ fn run_cmd_and_assert(cmd: &mut Command, should_pass: bool) {
     }
 
     assert_eq!(output.status.success(), should_pass);
+    captured
+}
+
+/// Provides the sorted file names of all results of the JSON summary printed using --output=json.
+fn analyzed_files(stdout: &[u8]) -> Vec<String> {
+    let summary: serde_json::Value = serde_json::from_slice(stdout).unwrap();
+    let mut names: Vec<_> = summary["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            let path = result["path"].as_str().unwrap();
+            path::Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Tests analyzing `test-err-fix.json` expect the finding in `module_fix.h`, which is modified by
+/// `invoke_arg_fix`. Such tests must hold this lock.
+static FIX_FILE: Mutex<()> = Mutex::new(());
+
+fn lock_fix_file() -> MutexGuard<'static, ()> {
+    // a failed test must not affect the other tests
+    FIX_FILE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn fix_file_macro_line() -> usize {
+    // the line of the macro that is reported by clang-tidy within the fixture
+    let header = crate_root_rel("test-files/c-demo/pkg_b/module_fix/module_fix.h");
+    let content = std::fs::read_to_string(header).expect("failed to read header");
+    content
+        .lines()
+        .position(|line| line.contains("#define MODULE_FIX_EXPRESSION"))
+        .expect("missing macro in header")
+        + 1
 }
 
 #[test]
@@ -103,6 +159,12 @@ fn invoke_json_and_bin() {
     let json = crate_root_rel("test-files/json/test-err-missing-build-root.json");
     run_cmd_and_assert(cmd().arg(json.as_os_str()), false);
 
+    // unknown sources and sources without compile commands are rejected
+    let json = crate_root_rel("test-files/json/test-err-source-invalid.json");
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), false);
+    let json = crate_root_rel("test-files/json/test-err-source-no-compile-commands.json");
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), false);
+
     let json = crate_root_rel("test-files/json/test-ok-empty-paths.json");
     // .json file with empty paths is accepted, but clang-tidy is not in the $PATH
     run_cmd_and_assert(cmd().arg(json.as_os_str()), false);
@@ -111,6 +173,39 @@ fn invoke_json_and_bin() {
     run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), true);
 }
 
+#[test]
+fn invoke_discover_config() {
+    // the configuration file is found in a parent directory
+    run_cmd_and_assert(
+        cmd_with_path().current_dir(crate_root_rel("test-files/c-demo/subfolder/pkg_c")),
+        true,
+    );
+    // no configuration file in any of the parent directories
+    run_cmd_and_assert(
+        cmd_with_path().current_dir(crate_root_rel("test-files/c-demo/pkg_a")),
+        false,
+    );
+}
+
+#[test]
+fn invoke_config_formats() {
+    let combinations = vec![
+        // same configuration as test-ok-tidy.json, including comments
+        ("test-files/json/test-ok-tidy.toml", true),
+        ("test-files/json/test-ok-tidy.yaml", true),
+        // the schema applies to all formats
+        ("test-files/json/test-err-format.yml", false),
+        // unsupported extension
+        ("test-files/json/test-err-format.ini", false),
+    ];
+
+    for test in combinations.into_iter() {
+        println!("checking {}", test.0);
+        let json = crate_root_rel(test.0);
+        run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), test.1);
+    }
+}
+
 #[test]
 fn invoke_json_tidy() {
     let combinations = vec![
@@ -118,14 +213,19 @@ fn invoke_json_tidy() {
         ("test-files/json/test-err-invalid-tidy-path.json", false),
         // path to tidyFile exists, but this is not a tidy file
         ("test-files/json/test-err-invalid-tidy-file.json", false),
-        // path to tidyFile exists, file has name ".clang-tidy", but no 'tidyRoot' exists
-        ("test-files/json/test-err-no-tidy-root.json", false),
+        // path to tidyFile exists, file has name ".clang-tidy", 'tidyRoot' is not needed for
+        // clang-tidy versions supporting --config-file
+        ("test-files/json/test-ok-no-tidy-root.json", true),
         // path to tidyFile exists, file has name ".clang-tidy", but 'tidyRoot' is an invalid path
         ("test-files/json/test-err-invalid-tidy-root.json", false),
         // path to tidyFile exists, file has name ".clang-tidy", and 'tidyRoot' exists
         ("test-files/json/test-ok-tidy.json", true),
         // path to tidyFile exists, file has name "named.clang-tidy", and 'tidyRoot' exists
         ("test-files/json/test-ok-tidy-named.json", true),
+        // inline tidy configuration
+        ("test-files/json/test-ok-tidy-config.json", true),
+        // inline tidy configuration and tidyFile are mutually exclusive
+        ("test-files/json/test-err-tidy-config-and-file.json", false),
     ];
 
     for test in combinations.into_iter() {
@@ -325,6 +425,7 @@ fn invoke_quiet() {
 
 #[test]
 fn invoke_arg_fix() {
+    let _lock = lock_fix_file();
     let json = crate_root_rel("test-files/json/test-err-fix.json");
     let fix_file = crate_root_rel("test-files/c-demo/pkg_b/module_fix/module_fix.h");
 
@@ -368,3 +469,750 @@ fn invoke_arg_fix() {
     println!("after all fixes have been applied, the check should pass");
     run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), true);
 }
+
+#[test]
+fn invoke_arg_export_fixes() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    let fix_file = crate_root_rel("test-files/c-demo/pkg_b/module_fix/module_fix.h");
+    let content = std::fs::read_to_string(&fix_file).unwrap();
+    let dir = std::env::temp_dir().join("run-clang-tidy-invoke-fixes");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // the fixes are exported but not applied, the execution therefore still fails
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg(format!("--export-fixes={}", dir.to_string_lossy())),
+        false,
+    );
+    assert!(dir.join("fixes.yaml").is_file());
+    assert_eq!(content, std::fs::read_to_string(&fix_file).unwrap());
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // fixes are either applied or exported
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--fix")
+            .arg(format!("--export-fixes={}", dir.to_string_lossy())),
+        false,
+    );
+}
+
+#[test]
+fn invoke_arg_dry_run() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    let fix_file = crate_root_rel("test-files/c-demo/pkg_b/module_fix/module_fix.h");
+    let content = std::fs::read_to_string(&fix_file).unwrap();
+    let patch = std::env::temp_dir().join("run-clang-tidy-invoke-dry-run.patch");
+    let _ = std::fs::remove_file(&patch);
+
+    // the fixes are written to the patch file, the sources are not modified
+    let output = run_cmd_and_capture(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--fix")
+            .arg("--dry-run")
+            .arg(format!("--patch-file={}", patch.to_string_lossy())),
+        false,
+    );
+    assert!(patch.is_file());
+
+    // all files with fixes are listed, including headers
+    assert!(output.contains("Files with fixes"));
+    assert!(output.contains("module_fix.h"));
+    assert_eq!(content, std::fs::read_to_string(&fix_file).unwrap());
+    let _ = std::fs::remove_file(&patch);
+
+    // a dry run requires --fix
+    run_cmd_and_assert(
+        cmd_with_path().arg(json.as_os_str()).arg("--dry-run"),
+        false,
+    );
+
+    // the diff must not be printed to stdout if it contains the JSON summary
+    let output = run_cmd_and_capture(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--fix")
+            .arg("--dry-run")
+            .arg("--output=json"),
+        false,
+    );
+    assert!(output.contains("--patch-file"));
+    assert_eq!(content, std::fs::read_to_string(&fix_file).unwrap());
+}
+
+#[test]
+fn invoke_report_sarif() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    let report = std::env::temp_dir().join("run-clang-tidy-invoke-report.sarif");
+    let _ = std::fs::remove_file(&report);
+
+    // the report format requires a report file and vice versa
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--report-format=sarif"),
+        false,
+    );
+
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--report-format=sarif")
+            .arg(format!("--report-file={}", report.to_string_lossy())),
+        false,
+    );
+
+    let content = std::fs::read_to_string(&report).expect("failed to read SARIF report");
+    let _ = std::fs::remove_file(&report);
+    let sarif: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!("2.1.0", sarif["version"]);
+
+    let run = &sarif["runs"][0];
+    assert_eq!("clang-tidy", run["tool"]["driver"]["name"]);
+    assert_eq!(false, run["invocations"][0]["executionSuccessful"]);
+
+    // the findings in the header are reported once and reference their rule
+    let results: Vec<_> = run["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["ruleId"] == "bugprone-macro-parentheses")
+        .collect();
+    assert!(!results.is_empty());
+
+    let line = fix_file_macro_line();
+    let mut locations = std::collections::HashSet::new();
+    for result in results {
+        assert_eq!("error", result["level"]);
+        assert_eq!(
+            "bugprone-macro-parentheses",
+            run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize]["id"]
+        );
+        let location = &result["locations"][0]["physicalLocation"];
+        assert!(location["artifactLocation"]["uri"]
+            .as_str()
+            .unwrap()
+            .ends_with("pkg_b/module_fix/module_fix.h"));
+        assert_eq!(line as u64, location["region"]["startLine"]);
+        assert!(
+            locations.insert((
+                location["region"]["startColumn"].clone(),
+                result["message"]["text"].clone()
+            )),
+            "duplicate result {result}"
+        );
+    }
+}
+
+#[test]
+fn invoke_report_junit() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    let report = std::env::temp_dir().join("run-clang-tidy-invoke-report.xml");
+    let _ = std::fs::remove_file(&report);
+
+    // only 'skipped' and 'failure' are supported for warnings
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--report-format=junit")
+            .arg(format!("--report-file={}", report.to_string_lossy()))
+            .arg("--junit-warnings=error"),
+        false,
+    );
+
+    // provides the test case of module_fix.c
+    let module_fix = |args: &[&str], should_pass: bool| {
+        run_cmd_and_assert(
+            cmd_with_path()
+                .arg(json.as_os_str())
+                .arg("--report-format=junit")
+                .arg(format!("--report-file={}", report.to_string_lossy()))
+                .args(args),
+            should_pass,
+        );
+        let content = std::fs::read_to_string(&report).expect("failed to read JUnit report");
+        let _ = std::fs::remove_file(&report);
+        assert!(content.starts_with("<?xml"));
+        assert!(!content.contains("tests=\"0\""));
+
+        let start = content
+            .find("module_fix/module_fix.c\"")
+            .expect("missing test case for module_fix.c");
+        let end = start + content[start..].find("</testcase>").unwrap();
+        content[start..end].to_string()
+    };
+
+    // errors are always reported as failure
+    let case = module_fix(&["--junit-warnings=skipped"], false);
+    assert!(case.contains("type=\"error\">"));
+    assert!(case.contains("bugprone-macro-parentheses"));
+
+    // warnings are reported depending on --junit-warnings
+    let case = module_fix(
+        &["--warnings-as-errors=-*", "--junit-warnings=failure"],
+        true,
+    );
+    assert!(case.contains("type=\"warning\">"));
+
+    let case = module_fix(
+        &["--warnings-as-errors=-*", "--junit-warnings=skipped"],
+        true,
+    );
+    assert!(case.contains("<skipped message="));
+    assert!(!case.contains("<failure"));
+}
+
+#[test]
+fn invoke_output_json() {
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    let output = cmd_with_path()
+        .arg(json.as_os_str())
+        .arg("--output=json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // stdout only contains the JSON summary, all other output is printed to stderr
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(1, summary["formatVersion"]);
+    assert_eq!(0, summary["totals"]["files"]);
+    assert!(summary["tidyFile"]
+        .as_str()
+        .unwrap()
+        .ends_with(".clang-tidy"));
+}
+
+#[test]
+fn invoke_env_and_overrides() {
+    let json = crate_root_rel("test-files/json/test-ok-env.json");
+    // references to undefined environment variables are rejected
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), false);
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .env("RUN_CLANG_TIDY_TEST_DEMO", "../c-demo"),
+        true,
+    );
+
+    // the paths are missing in the configuration but provided by the command line or environment
+    let json = crate_root_rel("test-files/json/test-ok-no-paths.json");
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), false);
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--paths=test-files/c-demo/does-not-exist/**/*.c"),
+        true,
+    );
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .env("RUN_CLANG_TIDY_PATHS", "a/**/*.c;b/**/*.c")
+            .env("RUN_CLANG_TIDY_CHECKS", "-*,bugprone-*")
+            .arg("--filter-pre=")
+            .arg("--extra-arg=-DUNIT_TEST"),
+        true,
+    );
+
+    // invalid values for overrides
+    for args in [
+        vec!["--source=headers"],
+        vec!["--tidy-config={\"check\": []}"],
+        vec!["--tidy-root=does-not-exist"],
+    ] {
+        run_cmd_and_assert(
+            cmd_with_path()
+                .arg(json.as_os_str())
+                .arg("--paths=none")
+                .args(args),
+            false,
+        );
+    }
+}
+
+#[test]
+fn invoke_extends() {
+    let combinations = vec![
+        // fields are inherited from the base configuration
+        ("test-files/json/test-ok-extends.json", true),
+        // the base configuration must exist
+        ("test-files/json/test-err-extends-missing.json", false),
+        // configurations must not extend themselves
+        ("test-files/json/test-err-extends-cyclic.json", false),
+    ];
+
+    for test in combinations.into_iter() {
+        println!("checking {}", test.0);
+        let json = crate_root_rel(test.0);
+        run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), test.1);
+    }
+}
+
+#[test]
+fn invoke_multiple_configs() {
+    let tidy = crate_root_rel("test-files/json/test-ok-tidy.json");
+    let extra_args = crate_root_rel("test-files/json/test-ok-extra-args.json");
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(tidy.as_os_str())
+            .arg(extra_args.as_os_str()),
+        true,
+    );
+
+    let combinations = vec![
+        // workspace listing the configurations of its members
+        ("test-files/json/test-ok-workspace.json", true),
+        // workspaces must not be members of other workspaces
+        ("test-files/json/test-err-workspace-nested.json", false),
+    ];
+    for test in combinations.into_iter() {
+        println!("checking {}", test.0);
+        let json = crate_root_rel(test.0);
+        run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), test.1);
+    }
+
+    // all configurations are listed in the combined summary
+    let workspace = crate_root_rel("test-files/json/test-ok-workspace.json");
+    let output = cmd_with_path()
+        .arg(workspace.as_os_str())
+        .arg("--output=json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(2, summary["configs"].as_array().unwrap().len());
+    assert!(summary["tidyFile"].is_null());
+}
+
+#[test]
+fn invoke_baseline() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    let baseline = std::env::temp_dir().join("run-clang-tidy-invoke-baseline.json");
+    let _ = std::fs::remove_file(&baseline);
+    let arg = format!("--baseline={}", baseline.to_string_lossy());
+
+    // a missing baseline leads to an error
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()).arg(&arg), false);
+
+    // the baseline must be specified for updates
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg("baseline")
+            .arg("update")
+            .arg(json.as_os_str()),
+        false,
+    );
+
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg("baseline")
+            .arg("update")
+            .arg(json.as_os_str())
+            .arg(&arg),
+        true,
+    );
+    let content = std::fs::read_to_string(&baseline).expect("failed to read baseline");
+    let mut model: serde_json::Value = serde_json::from_str(&content).unwrap();
+    let finding = model["findings"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|f| f["check"] == "bugprone-macro-parentheses")
+        .expect("missing finding in baseline");
+    assert_eq!("../c-demo/pkg_b/module_fix/module_fix.h", finding["file"]);
+    assert!(finding["count"].as_u64().unwrap() >= 1);
+
+    // known findings are suppressed, the execution passes
+    let output = run_cmd_and_capture(cmd_with_path().arg(json.as_os_str()).arg(&arg), true);
+    assert!(output.contains("Suppressed"));
+    assert!(!output.contains("have been fixed"));
+
+    // findings are relative to their configuration, also if analyzed with other configurations
+    let other = crate_root_rel("test-files/c-demo/subfolder/.run-clang-tidy.yaml");
+    let output = run_cmd_and_capture(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg(other.as_os_str())
+            .arg(&arg),
+        true,
+    );
+    assert!(output.contains("Suppressed"));
+
+    // a changed finding is reported as new, and the recorded finding as fixed
+    finding["fingerprint"] = serde_json::json!("0000000000000000");
+    std::fs::write(&baseline, model.to_string()).unwrap();
+    let output = run_cmd_and_capture(cmd_with_path().arg(json.as_os_str()).arg(&arg), false);
+    assert!(output.contains("have been fixed"));
+    assert!(output.contains("Execution failed for the following files"));
+    assert!(output.contains("module_fix.c"));
+
+    // invalid baseline files are rejected
+    std::fs::write(&baseline, "{}").unwrap();
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()).arg(&arg), false);
+    let _ = std::fs::remove_file(&baseline);
+}
+
+#[test]
+fn invoke_changed_since() {
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    // git is needed to determine the changed files
+    let path = std::env::join_paths(std::iter::once(crate_root().join("artifacts/clang")).chain(
+        std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()),
+    ))
+    .unwrap();
+
+    run_cmd_and_assert(
+        cmd()
+            .env("PATH", &path)
+            .arg(json.as_os_str())
+            .arg("--changed-since"),
+        true,
+    );
+
+    run_cmd_and_assert(
+        cmd()
+            .env("PATH", &path)
+            .arg(json.as_os_str())
+            .arg("--changed-since=does-not-exist"),
+        false,
+    );
+
+    // revisions must not be passed to git as options
+    let output = run_cmd_and_capture(
+        cmd()
+            .env("PATH", &path)
+            .arg(json.as_os_str())
+            .arg("--changed-since=--output=changed.txt"),
+        false,
+    );
+    assert!(output.contains("Invalid git revision"));
+}
+
+#[test]
+fn invoke_changed_headers() {
+    let dir = std::env::temp_dir().join("run-clang-tidy-invoke-changed");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    // the build root does not contain a compile_commands.json
+    std::fs::create_dir_all(dir.join("_bld")).unwrap();
+    std::fs::write(dir.join("src/a.c"), "#include \"a.h\"\n").unwrap();
+    std::fs::write(dir.join("src/a.h"), "").unwrap();
+    std::fs::write(dir.join("src/b.c"), "").unwrap();
+    std::fs::write(
+        dir.join("tidy.json"),
+        r#"{ "paths": ["./src/*.[ch]"], "buildRoot": "./_bld" }"#,
+    )
+    .unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@test"])
+            .args(args)
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "initial"]);
+
+    // the changed header is matched itself, untracked files are not matched at all
+    std::fs::write(dir.join("src/a.h"), "int a;\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+    let path = std::env::join_paths(std::iter::once(crate_root().join("artifacts/clang")).chain(
+        std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()),
+    ))
+    .unwrap();
+    let output = cmd()
+        .env("PATH", &path)
+        .arg(dir.join("tidy.json").as_os_str())
+        .arg("--changed-since")
+        .arg("--output=json")
+        .output()
+        .unwrap();
+    println!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success());
+
+    assert_eq!(vec!["a.c", "a.h"], analyzed_files(&output.stdout));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn invoke_cache_dir() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+    let cache_dir = std::env::temp_dir().join("run-clang-tidy-invoke-cache");
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    // provides the number of replayed and analyzed files
+    let replayed = || {
+        let output = run_cmd_and_capture(
+            cmd_with_path()
+                .arg(json.as_os_str())
+                .arg("--warnings-as-errors=-*")
+                .arg(format!("--cache-dir={}", cache_dir.to_string_lossy())),
+            true,
+        );
+        let line = output
+            .lines()
+            .find(|line| line.contains("Replayed "))
+            .expect("missing cache summary");
+        let counts: Vec<usize> = line
+            .split_whitespace()
+            .filter_map(|word| word.parse().ok())
+            .collect();
+        (counts[0], counts[1])
+    };
+
+    // the cache directory is created if it does not exist
+    let (cached, total) = replayed();
+    assert!(cache_dir.is_dir());
+    assert_eq!(0, cached);
+    assert!(total > 0);
+
+    // all results are replayed for unchanged files, including warnings
+    assert_eq!((total, total), replayed());
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[test]
+fn invoke_missing_commands() {
+    let json = crate_root_rel("test-files/json/test-ok-missing-commands.json");
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--missing-commands=ignore"),
+        false,
+    );
+
+    // provides the names of the analyzed files
+    let analyzed = |args: &[&str]| {
+        let output = cmd_with_path()
+            .arg(json.as_os_str())
+            .args(args)
+            .arg("--output=json")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        println!("{stderr}");
+        assert!(output.status.success());
+
+        (analyzed_files(&output.stdout), stderr)
+    };
+
+    // files are not checked by default
+    let (names, stderr) = analyzed(&[]);
+    assert_eq!(vec!["module_a.c", "module_a.h"], names);
+    assert!(!stderr.contains("have no compile command"));
+
+    let (names, stderr) = analyzed(&["--missing-commands=skip"]);
+    assert_eq!(vec!["module_a.c"], names);
+    assert!(!stderr.contains("have no compile command"));
+
+    let (names, stderr) = analyzed(&["--missing-commands=warn"]);
+    assert_eq!(vec!["module_a.c", "module_a.h"], names);
+    assert!(stderr.contains("have no compile command"));
+
+    let output = run_cmd_and_capture(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--missing-commands=fail"),
+        false,
+    );
+    assert!(output.contains("The following files have no compile command"));
+    assert!(output.contains("module_a.h"));
+}
+
+#[test]
+fn invoke_extra_args() {
+    let json = crate_root_rel("test-files/json/test-ok-extra-args.json");
+    run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), true);
+
+    // arguments following "--" are passed to clang-tidy
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--")
+            .arg("--quiet")
+            .arg("--system-headers"),
+        true,
+    );
+}
+
+#[test]
+fn invoke_path_groups() {
+    let combinations = vec![
+        // groups with their own checks, arguments, and tidy file
+        ("test-files/json/test-ok-path-groups.json", true),
+        // unknown fields within groups are rejected
+        ("test-files/json/test-err-path-group-invalid.json", false),
+    ];
+
+    for test in combinations.into_iter() {
+        println!("checking {}", test.0);
+        let json = crate_root_rel(test.0);
+        run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()), test.1);
+    }
+}
+
+#[test]
+fn invoke_init() {
+    let dir = std::env::temp_dir().join("run-clang-tidy-invoke-init");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("src/module")).unwrap();
+    std::fs::create_dir_all(dir.join("_bld/out")).unwrap();
+    std::fs::write(dir.join("src/module/module.c"), "").unwrap();
+    std::fs::write(dir.join("_bld/out/compile_commands.json"), "[]").unwrap();
+    std::fs::write(dir.join(".clang-tidy"), "").unwrap();
+
+    run_cmd_and_assert(cmd().arg("init").arg(dir.as_os_str()), true);
+    let content = std::fs::read_to_string(dir.join("run-clang-tidy.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(json["paths"], serde_json::json!(["./src/**/*.c"]));
+    assert_eq!(json["tidyFile"], "./.clang-tidy");
+    assert_eq!(json["buildRoot"], "./_bld/out");
+
+    // existing configuration files are only replaced with --force
+    run_cmd_and_assert(cmd().arg("init").arg(dir.as_os_str()), false);
+    run_cmd_and_assert(cmd().arg("init").arg("--force").arg(dir.as_os_str()), true);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn invoke_check_config() {
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    let output = cmd_with_path()
+        .arg("check-config")
+        .arg(json.as_os_str())
+        .arg("--paths=test-files/c-demo/pkg_a/**/*.c")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // the files are listed, but clang-tidy is not executed
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("pkg_a/module_a/module_a.c"));
+    assert!(!stderr.contains("Executing clang-tidy"));
+
+    // all configurations are checked, the check fails if any of them is invalid
+    let invalid = crate_root_rel("test-files/json/test-err-invalid-tidy-root.json");
+    run_cmd_and_assert(
+        cmd_with_path().arg("check-config").arg(invalid.as_os_str()),
+        false,
+    );
+    run_cmd_and_assert(
+        cmd_with_path()
+            .arg("check-config")
+            .arg(json.as_os_str())
+            .arg(invalid.as_os_str()),
+        false,
+    );
+}
+
+#[test]
+fn invoke_fail_fast() {
+    let _lock = lock_fix_file();
+    let json = crate_root_rel("test-files/json/test-err-fix.json");
+
+    // without limit, all files are analyzed
+    let output = cmd_with_path()
+        .arg(json.as_os_str())
+        .arg("--output=json")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let total = analyzed_files(&output.stdout).len();
+
+    // module_fix.c fails, the files following it are not analyzed when using a single job
+    for args in [vec!["--fail-fast"], vec!["--max-failures=1"]] {
+        let output = cmd_with_path()
+            .arg(json.as_os_str())
+            .args(&args)
+            .args(["-j", "1", "--output=json"])
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        println!("{stderr}");
+        assert!(!output.status.success());
+        assert!(stderr.contains("Stopped after 1 failed files"));
+        assert!(stderr.contains("files have not been analyzed"));
+
+        let analyzed = analyzed_files(&output.stdout);
+        assert!(analyzed.contains(&"module_fix.c".to_string()));
+        assert!(analyzed.len() < total);
+    }
+
+    // the limit is not reached if fewer files fail
+    let output = run_cmd_and_capture(
+        cmd_with_path()
+            .arg(json.as_os_str())
+            .arg("--max-failures=2"),
+        false,
+    );
+    assert!(!output.contains("have not been analyzed"));
+
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    for (args, should_pass) in [
+        // the execution does not stop if no file fails
+        (vec!["--fail-fast"], true),
+        (vec!["--max-failures=1"], true),
+        // invalid maximum number of failures
+        (vec!["--max-failures=0"], false),
+        (vec!["--max-failures=-1"], false),
+        (vec!["--fail-fast", "--max-failures=2"], false),
+    ] {
+        run_cmd_and_assert(
+            cmd_with_path().arg(json.as_os_str()).args(args),
+            should_pass,
+        );
+    }
+}
+
+#[test]
+fn invoke_timeout() {
+    let json = crate_root_rel("test-files/json/test-ok-tidy.json");
+    run_cmd_and_assert(
+        cmd_with_path().arg(json.as_os_str()).arg("--timeout=600"),
+        true,
+    );
+
+    // invalid timeouts
+    for arg in ["--timeout=0", "--timeout=-1", "--timeout=abc"] {
+        run_cmd_and_assert(cmd_with_path().arg(json.as_os_str()).arg(arg), false);
+    }
+}
+
+#[test]
+#[cfg(not(windows))]
+fn invoke_timeout_terminates() {
+    let json = crate_root_rel("test-files/json/test-ok-missing-commands.json");
+    let command = crate_root_rel("test-files/clang-tidy-hang/clang-tidy");
+
+    let start = time::Instant::now();
+    let output = cmd()
+        .arg(json.as_os_str())
+        .arg(format!("--command={}", command.to_string_lossy()))
+        .args(["--timeout=1", "-j", "2", "--output=json"])
+        .output()
+        .unwrap();
+    println!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!output.status.success());
+    assert!(start.elapsed() < time::Duration::from_secs(20));
+
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = summary["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results.iter().all(|result| result["outcome"] == "timeout"));
+    assert_eq!(results.len(), summary["totals"]["timeouts"]);
+    assert_eq!(0, summary["totals"]["errors"]);
+}